@@ -0,0 +1,21 @@
+//! Runtime API definition for the ERC20 pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use erc20::{SnapshotId, TokenId, TransferError};
+
+sp_api::decl_runtime_apis! {
+	pub trait Erc20Api<AccountId, TokenBalance> where
+		AccountId: Codec,
+		TokenBalance: Codec,
+	{
+		/// Check whether `from` could send `amount` of token `id` to `to`, and why not if it
+		/// could not.
+		fn can_transfer(id: TokenId, from: AccountId, to: AccountId, amount: TokenBalance) -> Result<(), TransferError>;
+		/// Return the balance `who` held of token `id` at `snapshot`, if that snapshot was taken.
+		fn balance_of_at(id: TokenId, who: AccountId, snapshot: SnapshotId) -> Option<TokenBalance>;
+		/// Return the total supply of token `id` at `snapshot`, if that snapshot was taken.
+		fn total_supply_at(id: TokenId, snapshot: SnapshotId) -> Option<TokenBalance>;
+	}
+}
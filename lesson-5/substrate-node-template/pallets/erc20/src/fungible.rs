@@ -0,0 +1,93 @@
+//! Single-token views over the [`fungibles`](crate::fungibles) traits.
+//!
+//! [`ItemOf`] fixes the asset id, so a pallet that expects one currency can be configured with
+//! one token of this pallet, much like it would be with `pallet_balances`.
+
+use crate::fungibles;
+use frame_support::{
+    dispatch::{DispatchError, DispatchResult},
+    traits::Get,
+};
+use sp_std::marker::PhantomData;
+
+/// Read-only access to the balances and supply of one token.
+pub trait Inspect<AccountId> {
+    type Balance;
+
+    fn total_issuance() -> Self::Balance;
+    fn minimum_balance() -> Self::Balance;
+    fn balance(who: &AccountId) -> Self::Balance;
+    fn reducible_balance(who: &AccountId, keep_alive: bool) -> Self::Balance;
+}
+
+/// Creating and destroying one token.
+pub trait Mutate<AccountId>: Inspect<AccountId> {
+    fn mint_into(who: &AccountId, amount: Self::Balance) -> DispatchResult;
+    fn burn_from(who: &AccountId, amount: Self::Balance) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Moving one token between accounts.
+pub trait Transfer<AccountId>: Inspect<AccountId> {
+    fn transfer(
+        source: &AccountId,
+        dest: &AccountId,
+        amount: Self::Balance,
+        keep_alive: bool,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Treats the asset `A` of the multi-asset implementation `F` as a single currency.
+pub struct ItemOf<F, A, AccountId>(PhantomData<(F, A, AccountId)>);
+
+impl<F, A, AccountId> Inspect<AccountId> for ItemOf<F, A, AccountId>
+where
+    F: fungibles::Inspect<AccountId>,
+    A: Get<F::AssetId>,
+{
+    type Balance = F::Balance;
+
+    fn total_issuance() -> Self::Balance {
+        F::total_issuance(A::get())
+    }
+
+    fn minimum_balance() -> Self::Balance {
+        F::minimum_balance(A::get())
+    }
+
+    fn balance(who: &AccountId) -> Self::Balance {
+        F::balance(A::get(), who)
+    }
+
+    fn reducible_balance(who: &AccountId, keep_alive: bool) -> Self::Balance {
+        F::reducible_balance(A::get(), who, keep_alive)
+    }
+}
+
+impl<F, A, AccountId> Mutate<AccountId> for ItemOf<F, A, AccountId>
+where
+    F: fungibles::Mutate<AccountId>,
+    A: Get<F::AssetId>,
+{
+    fn mint_into(who: &AccountId, amount: Self::Balance) -> DispatchResult {
+        F::mint_into(A::get(), who, amount)
+    }
+
+    fn burn_from(who: &AccountId, amount: Self::Balance) -> Result<Self::Balance, DispatchError> {
+        F::burn_from(A::get(), who, amount)
+    }
+}
+
+impl<F, A, AccountId> Transfer<AccountId> for ItemOf<F, A, AccountId>
+where
+    F: fungibles::Transfer<AccountId>,
+    A: Get<F::AssetId>,
+{
+    fn transfer(
+        source: &AccountId,
+        dest: &AccountId,
+        amount: Self::Balance,
+        keep_alive: bool,
+    ) -> Result<Self::Balance, DispatchError> {
+        F::transfer(A::get(), source, dest, amount, keep_alive)
+    }
+}
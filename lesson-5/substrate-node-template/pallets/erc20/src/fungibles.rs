@@ -0,0 +1,101 @@
+//! Traits for working with several fungible tokens, keyed by an asset id.
+//!
+//! They follow the shape of `frame_support::traits::tokens::fungibles` from later Substrate
+//! releases, which this one does not ship yet, so a pallet written against them can move to the
+//! upstream traits with little more than a change of imports.
+
+use crate::{Module, Trait, TokenId};
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use sp_runtime::traits::{Saturating, Zero};
+
+/// Read-only access to balances and supply.
+pub trait Inspect<AccountId> {
+    type AssetId;
+    type Balance;
+
+    /// Amount of `asset` in existence.
+    fn total_issuance(asset: Self::AssetId) -> Self::Balance;
+    /// Smallest balance an account may hold without being reaped.
+    fn minimum_balance(asset: Self::AssetId) -> Self::Balance;
+    /// Amount of `asset` held by `who`.
+    fn balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance;
+    /// Part of `who`'s balance that can be moved out right now.
+    fn reducible_balance(asset: Self::AssetId, who: &AccountId, keep_alive: bool) -> Self::Balance;
+}
+
+/// Creating and destroying tokens.
+pub trait Mutate<AccountId>: Inspect<AccountId> {
+    /// Create `amount` of `asset` and credit it to `who`.
+    fn mint_into(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+    /// Destroy `amount` of `asset` from `who`, returning the amount destroyed.
+    fn burn_from(
+        asset: Self::AssetId,
+        who: &AccountId,
+        amount: Self::Balance,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Moving tokens between accounts.
+pub trait Transfer<AccountId>: Inspect<AccountId> {
+    /// Move `amount` of `asset` from `source` to `dest`, returning the amount moved.
+    fn transfer(
+        asset: Self::AssetId,
+        source: &AccountId,
+        dest: &AccountId,
+        amount: Self::Balance,
+        keep_alive: bool,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+impl<T: Trait> Inspect<T::AccountId> for Module<T> {
+    type AssetId = TokenId;
+    type Balance = T::TokenBalance;
+
+    fn total_issuance(asset: TokenId) -> T::TokenBalance {
+        Self::token_details(asset)
+            .map(|token| token.total_supply)
+            .unwrap_or_else(Zero::zero)
+    }
+
+    fn minimum_balance(_asset: TokenId) -> T::TokenBalance {
+        Zero::zero()
+    }
+
+    fn balance(asset: TokenId, who: &T::AccountId) -> T::TokenBalance {
+        Self::balance_of(asset, who)
+    }
+
+    fn reducible_balance(asset: TokenId, who: &T::AccountId, _keep_alive: bool) -> T::TokenBalance {
+        if Self::is_paused(asset) || Self::is_frozen(asset, who) || Self::is_blocked(asset, who) {
+            return Zero::zero();
+        }
+        Self::balance_of(asset, who).saturating_sub(Self::locked(asset, who))
+    }
+}
+
+impl<T: Trait> Mutate<T::AccountId> for Module<T> {
+    fn mint_into(asset: TokenId, who: &T::AccountId, amount: T::TokenBalance) -> DispatchResult {
+        Self::_mint(asset, who.clone(), amount)
+    }
+
+    fn burn_from(
+        asset: TokenId,
+        who: &T::AccountId,
+        amount: T::TokenBalance,
+    ) -> Result<T::TokenBalance, DispatchError> {
+        Self::_burn(asset, who.clone(), amount).map(|_| amount)
+    }
+}
+
+impl<T: Trait> Transfer<T::AccountId> for Module<T> {
+    fn transfer(
+        asset: TokenId,
+        source: &T::AccountId,
+        dest: &T::AccountId,
+        amount: T::TokenBalance,
+        _keep_alive: bool,
+    ) -> Result<T::TokenBalance, DispatchError> {
+        Self::ensure_token(asset)?;
+        Self::_transfer(asset, source.clone(), dest.clone(), amount).map(|_| amount)
+    }
+}
@@ -1,12 +1,34 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 use codec::{Codec, Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, Parameter,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    traits::{EnsureOrigin, Get, ModuleToIndex},
+    Parameter,
 };
 use frame_system::{self as system, ensure_signed};
-use sp_runtime::traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedSub, Member};
+use sp_runtime::traits::{
+    AtLeast32BitUnsigned, CheckedAdd, CheckedMul, CheckedSub, IdentifyAccount, Member, SaturatedConversion,
+    Saturating, Verify, Zero,
+};
 use sp_std::prelude::*;
 
+pub mod fungible;
+pub mod fungibles;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Identifier of a token issued by this pallet.
+pub type TokenId = u32;
+
+/// Identifier of a balance snapshot of one token. Snapshots are numbered from 1.
+pub type SnapshotId = u32;
+
 pub trait Trait: system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
     type TokenBalance: CheckedAdd
@@ -17,95 +39,752 @@ pub trait Trait: system::Trait {
         + Default
         + Copy
         + AtLeast32BitUnsigned;
+    /// Origin that can manage the roles of any token, besides the token's own admins.
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+    /// Signature owners use to approve spenders off chain with `permit`.
+    type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+    type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Most vesting schedules an account can have at once for one token.
+    type MaxVestingSchedules: Get<u32>;
+    /// Most blocks a vesting schedule can wait before it starts.
+    type MaxVestingDelay: Get<Self::BlockNumber>;
+    /// Most blocks a vesting schedule can take to fully vest once started.
+    type MaxVestingDuration: Get<Self::BlockNumber>;
 }
+
+/// Tag that keeps `permit` signatures from being valid for any other message.
+pub const PERMIT_TAG: &[u8] = b"erc20/permit";
+
+/// Build the message an owner signs for `permit`, for wallets and other client tooling.
+///
+/// `pallet_index` is the position of this pallet in the runtime's `construct_runtime!`.
+#[allow(clippy::too_many_arguments)]
+pub fn encode_permit<AccountId: Encode, Balance: Encode, BlockNumber: Encode, Hash: Encode>(
+    genesis_hash: &Hash,
+    pallet_index: u32,
+    id: TokenId,
+    owner: &AccountId,
+    spender: &AccountId,
+    value: &Balance,
+    nonce: u64,
+    deadline: &BlockNumber,
+) -> Vec<u8> {
+    (
+        PERMIT_TAG,
+        genesis_hash,
+        pallet_index,
+        id,
+        owner,
+        spender,
+        value,
+        nonce,
+        deadline,
+    )
+        .encode()
+}
+
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
 pub struct Erc20Token<U> {
     name: Vec<u8>,
     ticker: Vec<u8>,
     total_supply: U,
+    /// Smallest amount `vested_transfer` accepts for this token. Set by its admins.
+    min_vested_transfer: U,
+}
+
+/// Permission an account can hold on a single token.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    /// Grants and revokes roles.
+    Admin,
+    /// Creates new tokens with `mint`.
+    Minter,
+    /// Destroys tokens with `burn` and `burn_from`.
+    Burner,
+}
+
+/// Tokens of one account that unlock in equal parts every `period` blocks from `start`.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct VestingSchedule<Balance, BlockNumber> {
+    /// Amount the schedule locked when it was created.
+    pub amount: Balance,
+    pub start: BlockNumber,
+    /// Blocks between two unlocks.
+    pub period: BlockNumber,
+    /// Number of unlocks. The last one also releases what division left over.
+    pub period_count: u32,
+}
+
+impl<Balance, BlockNumber> VestingSchedule<Balance, BlockNumber>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    BlockNumber: AtLeast32BitUnsigned + Copy,
+{
+    /// Amount still locked at block `now`.
+    pub fn locked_at(&self, now: BlockNumber) -> Balance {
+        if now < self.start {
+            return self.amount;
+        }
+        let periods = ((now - self.start) / self.period).saturated_into::<u32>();
+        if periods >= self.period_count {
+            return Zero::zero();
+        }
+        let per_period = self.amount / Balance::from(self.period_count);
+        self.amount - per_period * Balance::from(periods)
+    }
+}
+
+/// Why `can_transfer` would refuse a transfer, or a mint or burn would be refused.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransferError {
+    UnknownToken,
+    /// The token is paused.
+    Paused,
+    SenderFrozen,
+    SenderBlocked,
+    RecipientBlocked,
+    InsufficientBalance,
+    /// The sender's balance is enough, but part of it is still vesting.
+    Locked,
+    /// The recipient's balance would overflow.
+    Overflow,
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Erc20{
-        Tokens get(fn token_details): Erc20Token<T::TokenBalance>;
-        Balanceof get(fn balance_of): map hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
-        Allowance get(fn allowance): map hasher(blake2_128_concat)  (T::AccountId,T::AccountId) => T::TokenBalance;
+        Tokens get(fn token_details): map hasher(twox_64_concat) TokenId => Option<Erc20Token<T::TokenBalance>>;
+        NextTokenId get(fn next_token_id): TokenId;
+        Balanceof get(fn balance_of): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+        Roles get(fn has_role): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) (Role,T::AccountId) => bool;
+        /// Amount `spender` may still move out of `owner`'s balance, keyed by `(owner, spender)`.
+        Allowance get(fn allowance): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) (T::AccountId,T::AccountId) => T::TokenBalance;
+        PausedTokens get(fn is_paused): map hasher(twox_64_concat) TokenId => bool;
+        /// Accounts that cannot send the token, though they can still receive it.
+        FrozenAccounts get(fn is_frozen): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => bool;
+        /// Accounts that can neither send, receive nor approve the token.
+        Blocklist get(fn is_blocked): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => bool;
+        /// Latest snapshot taken of each token, 0 if there is none.
+        CurrentSnapshot get(fn current_snapshot): map hasher(twox_64_concat) TokenId => SnapshotId;
+        /// Balances as they were before the first change after each snapshot, oldest first.
+        ///
+        /// An entry `(s, b)` means the balance was `b` at every snapshot after the previous
+        /// entry's, up to and including `s`.
+        BalanceSnapshots: double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => Vec<(SnapshotId,T::TokenBalance)>;
+        /// Total supply recorded the same way as `BalanceSnapshots`.
+        SupplySnapshots: map hasher(twox_64_concat) TokenId => Vec<(SnapshotId,T::TokenBalance)>;
+        /// Vesting schedules of each account that have not fully vested, at most `MaxVestingSchedules`.
+        Vesting get(fn vesting): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => Vec<VestingSchedule<T::TokenBalance,T::BlockNumber>>;
+        /// Part of each balance that cannot be moved yet. Updated by `vest`.
+        Locked get(fn locked): double_map hasher(twox_64_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::TokenBalance;
+        /// Next nonce each owner must sign into a `permit`.
+        PermitNonces get(fn permit_nonce): map hasher(blake2_128_concat) T::AccountId => u64;
     }
 }
 decl_event! (
     pub enum Event<T> where AccountId = <T as system::Trait>::AccountId, <T as Trait>::TokenBalance  {
-        Transfer(AccountId,AccountId,TokenBalance),
-        Approval(AccountId,AccountId,TokenBalance),
+        TokenCreated(TokenId,AccountId,TokenBalance),
+        Transfer(TokenId,AccountId,AccountId,TokenBalance),
+        /// The amount was minted to the account.
+        Minted(TokenId,AccountId,TokenBalance),
+        /// The amount was burned from the account.
+        Burned(TokenId,AccountId,TokenBalance),
+        Approval(TokenId,AccountId,AccountId,TokenBalance),
+        RoleGranted(TokenId,Role,AccountId),
+        RoleRevoked(TokenId,Role,AccountId),
+        Paused(TokenId),
+        Unpaused(TokenId),
+        Frozen(TokenId,AccountId),
+        Thawed(TokenId,AccountId),
+        Blocked(TokenId,AccountId),
+        Unblocked(TokenId,AccountId),
+        Snapshot(TokenId,SnapshotId),
+        /// The smallest amount `vested_transfer` accepts for the token was set.
+        MinVestedTransferSet(TokenId,TokenBalance),
+        /// A vesting schedule locking the amount was created for the account.
+        VestingCreated(TokenId,AccountId,TokenBalance),
+        /// The account's locked balance went down to the amount.
+        Vested(TokenId,AccountId,TokenBalance),
+        VestingCompleted(TokenId,AccountId),
     }
 );
 decl_error! {
     pub enum Error for Module<T: Trait> {
         Storageoverflow,
+        /// No token has been created under this id.
+        UnknownToken,
+        /// Every token id has been allocated.
+        NoAvailableTokenId,
+        /// The sender does not hold enough tokens.
+        InsufficientBalance,
+        /// The caller has not been approved to spend this much.
+        InsufficientAllowance,
+        /// The caller does not hold the role this call requires.
+        MissingRole,
+        /// The permit's deadline has passed.
+        PermitExpired,
+        /// The permit was not signed by the owner over the expected payload.
+        BadSignature,
+        /// Transfers and approvals of the token are paused.
+        TokenPaused,
+        /// The sender's account is frozen.
+        AccountFrozen,
+        /// An account involved is on the blocklist.
+        AccountBlocked,
+        /// Every snapshot id of the token has been used.
+        NoAvailableSnapshotId,
+        /// Part of the balance is still vesting.
+        BalanceLocked,
+        /// The recipient already has `MaxVestingSchedules` vesting schedules for this token.
+        TooManyVestingSchedules,
+        /// The amount is below the token's minimum vested transfer.
+        VestedTransferTooLow,
+        /// A vesting schedule needs a non-zero period and period count, must start within
+        /// `MaxVestingDelay` blocks and fully vest within `MaxVestingDuration` blocks.
+        InvalidVestingSchedule,
+        /// The account has no vesting schedule for this token.
+        NotVesting,
+        /// The runtime does not know the index of this module, which permits are bound to.
+        UnknownModuleIndex,
+    }
+}
+
+impl<T: Trait> From<TransferError> for Error<T> {
+    fn from(error: TransferError) -> Self {
+        match error {
+            TransferError::UnknownToken => Error::<T>::UnknownToken,
+            TransferError::Paused => Error::<T>::TokenPaused,
+            TransferError::SenderFrozen => Error::<T>::AccountFrozen,
+            TransferError::SenderBlocked | TransferError::RecipientBlocked => {
+                Error::<T>::AccountBlocked
+            }
+            TransferError::InsufficientBalance => Error::<T>::InsufficientBalance,
+            TransferError::Locked => Error::<T>::BalanceLocked,
+            TransferError::Overflow => Error::<T>::Storageoverflow,
+        }
     }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         type Error = Error<T>;
+
+        /// Most vesting schedules an account can have at once for one token.
+        const MaxVestingSchedules: u32 = T::MaxVestingSchedules::get();
+        /// Most blocks a vesting schedule can wait before it starts.
+        const MaxVestingDelay: T::BlockNumber = T::MaxVestingDelay::get();
+        /// Most blocks a vesting schedule can take to fully vest once started.
+        const MaxVestingDuration: T::BlockNumber = T::MaxVestingDuration::get();
+
         fn deposit_event()=default;
         #[weight=0]
-        fn init(origin, name: Vec<u8>, ticker: Vec<u8>, total_supply: T::TokenBalance)->DispatchResult {
+        fn create_token(origin, name: Vec<u8>, ticker: Vec<u8>, total_supply: T::TokenBalance)->DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(name.len()<=64,"token name cannot exceed 64 bytes");
             ensure!(ticker.len()<=32,"token ticker cannot exceed 32 bytes");
+            let id = Self::next_token_id();
+            ensure!(!<Tokens<T>>::contains_key(id),Error::<T>::NoAvailableTokenId);
+            let next_id = id.checked_add(1).ok_or(Error::<T>::NoAvailableTokenId)?;
             let token = Erc20Token {
                 name,
                 ticker,
                 total_supply,
+                min_vested_transfer: Zero::zero(),
             };
-            <Tokens<T>>::set(token);
-            <Balanceof<T>>::insert(sender,total_supply);
+            <Tokens<T>>::insert(id,token);
+            NextTokenId::put(next_id);
+            <Balanceof<T>>::insert(id,sender.clone(),total_supply);
+            <Roles<T>>::insert(id,(Role::Admin,sender.clone()),true);
+            Self::deposit_event(RawEvent::TokenCreated(id,sender,total_supply));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn transfer(_origin,id: TokenId,to: T::AccountId,value: T::TokenBalance)->DispatchResult{
+            let sender = ensure_signed(_origin)?;
+            Self::ensure_token(id)?;
+            Self::_transfer(id,sender,to,value)
+        }
+        #[weight=0]
+        pub fn transfer_from(_origin,id: TokenId,from: T::AccountId,to: T::AccountId,value: T::TokenBalance)->DispatchResult{
+            let spender = ensure_signed(_origin)?;
+            Self::ensure_token(id)?;
+            ensure!(!Self::is_blocked(id,&spender),Error::<T>::AccountBlocked);
+            let allowance =Self::allowance(id,(from.clone(),spender.clone()));
+            let updated_allowance = allowance.checked_sub(&value).ok_or(Error::<T>::InsufficientAllowance)?;
+            Self::_transfer(id,from.clone(),to,value)?;
+            Self::_approve(id,from,spender,updated_allowance);
             Ok(())
         }
 
+        /// Set the allowance of `spender` over the caller's tokens to `value`, replacing any previous one.
         #[weight=0]
-        fn transfer(_origin,to: T::AccountId,value: T::TokenBalance)->DispatchResult{
+        fn approve(_origin,id: TokenId,spender: T::AccountId,value: T::TokenBalance)->DispatchResult{
             let sender = ensure_signed(_origin)?;
-            Self::_transfer(sender,to,value)
+            Self::ensure_can_approve(id,&sender,&spender)?;
+            Self::_approve(id,sender,spender,value);
+            Ok(())
         }
+
         #[weight=0]
-        pub fn transfer_from(_origin,from: T::AccountId,to: T::AccountId,value: T::TokenBalance)->DispatchResult{
-            let allowance =Self::allowance((from.clone(),to.clone()));
-            ensure!(allowance>=value,"Not enough allowance.");
-            let updated_allowance = allowance.checked_sub(&value).ok_or(Error::<T>::Storageoverflow)?;
-            <Allowance<T>>::insert((from.clone(),to.clone()),updated_allowance);
-            Self::deposit_event(RawEvent::Approval(from.clone(),to.clone(),value));
-            Self::_transfer(from,to,value)
+        fn increase_allowance(_origin,id: TokenId,spender: T::AccountId,added_value: T::TokenBalance)->DispatchResult{
+            let sender = ensure_signed(_origin)?;
+            Self::ensure_can_approve(id,&sender,&spender)?;
+            let allowance = Self::allowance(id,(sender.clone(),spender.clone()));
+            let updated_allowance = allowance.checked_add(&added_value).ok_or(Error::<T>::Storageoverflow)?;
+            Self::_approve(id,sender,spender,updated_allowance);
+            Ok(())
         }
 
         #[weight=0]
-        fn approve(_origin,spender: T::AccountId,value: T::TokenBalance)->DispatchResult{
+        fn decrease_allowance(_origin,id: TokenId,spender: T::AccountId,subtracted_value: T::TokenBalance)->DispatchResult{
             let sender = ensure_signed(_origin)?;
-            let allowance = Self::allowance((sender.clone(),spender.clone()));
-            let updated_allowance=allowance + value;
-            <Allowance<T>>::insert((sender.clone(),spender.clone()),updated_allowance);
-            Self::deposit_event(RawEvent::Approval(sender.clone(),spender.clone(),value));
+            Self::ensure_can_approve(id,&sender,&spender)?;
+            let allowance = Self::allowance(id,(sender.clone(),spender.clone()));
+            let updated_allowance = allowance.checked_sub(&subtracted_value).ok_or(Error::<T>::InsufficientAllowance)?;
+            Self::_approve(id,sender,spender,updated_allowance);
+            Ok(())
+        }
+
+        /// Set an allowance from a signature by `owner`, so a relayer can pay the fees for it.
+        ///
+        /// The signature covers the payload built by `encode_permit` with the owner's next
+        /// nonce, and is only accepted up to block `deadline`.
+        #[weight=0]
+        fn permit(origin,id: TokenId,owner: T::AccountId,spender: T::AccountId,value: T::TokenBalance,deadline: T::BlockNumber,signature: T::OffchainSignature)->DispatchResult{
+            ensure_signed(origin)?;
+            Self::ensure_can_approve(id,&owner,&spender)?;
+            ensure!(<system::Module<T>>::block_number() <= deadline,Error::<T>::PermitExpired);
+            let nonce = Self::permit_nonce(&owner);
+            let payload = Self::permit_message(id,&owner,&spender,&value,nonce,&deadline)?;
+            ensure!(signature.verify(&payload[..],&owner),Error::<T>::BadSignature);
+            <PermitNonces<T>>::insert(&owner,nonce.wrapping_add(1));
+            Self::_approve(id,owner,spender,value);
+            Ok(())
+        }
+
+        /// Grant `role` on token `id`. Callable by the token's admins or `AdminOrigin`.
+        #[weight=0]
+        fn grant_role(origin,id: TokenId,role: Role,who: T::AccountId)->DispatchResult{
+            Self::ensure_token(id)?;
+            Self::ensure_admin(origin,id)?;
+            <Roles<T>>::insert(id,(role,who.clone()),true);
+            Self::deposit_event(RawEvent::RoleGranted(id,role,who));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn revoke_role(origin,id: TokenId,role: Role,who: T::AccountId)->DispatchResult{
+            Self::ensure_token(id)?;
+            Self::ensure_admin(origin,id)?;
+            <Roles<T>>::remove(id,(role,who.clone()));
+            Self::deposit_event(RawEvent::RoleRevoked(id,role,who));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn mint(origin,id: TokenId,to: T::AccountId,amount: T::TokenBalance)->DispatchResult{
+            let sender = ensure_signed(origin)?;
+            Self::ensure_token(id)?;
+            ensure!(Self::has_role(id,(Role::Minter,sender)),Error::<T>::MissingRole);
+            Self::_mint(id,to,amount)
+        }
+
+        #[weight=0]
+        fn burn(origin,id: TokenId,amount: T::TokenBalance)->DispatchResult{
+            let sender = ensure_signed(origin)?;
+            Self::ensure_token(id)?;
+            ensure!(Self::has_role(id,(Role::Burner,sender.clone())),Error::<T>::MissingRole);
+            Self::_burn(id,sender,amount)
+        }
+
+        /// Burn `amount` of `from`'s tokens, spending the caller's allowance like `transfer_from`.
+        #[weight=0]
+        fn burn_from(origin,id: TokenId,from: T::AccountId,amount: T::TokenBalance)->DispatchResult{
+            let spender = ensure_signed(origin)?;
+            Self::ensure_token(id)?;
+            ensure!(Self::has_role(id,(Role::Burner,spender.clone())),Error::<T>::MissingRole);
+            ensure!(!Self::is_blocked(id,&spender),Error::<T>::AccountBlocked);
+            let allowance = Self::allowance(id,(from.clone(),spender.clone()));
+            let updated_allowance = allowance.checked_sub(&amount).ok_or(Error::<T>::InsufficientAllowance)?;
+            Self::_burn(id,from.clone(),amount)?;
+            Self::_approve(id,from,spender,updated_allowance);
+            Ok(())
+        }
+
+        /// Send `amount` to `to`, locked until it vests in `period_count` equal parts, one every
+        /// `period` blocks from block `start`.
+        ///
+        /// The schedule is added to those `to` already has, up to `MaxVestingSchedules`. Only the
+        /// token's admins can vest, so that no one else can fill an account's schedules.
+        #[weight=0]
+        fn vested_transfer(origin,id: TokenId,to: T::AccountId,amount: T::TokenBalance,start: T::BlockNumber,period: T::BlockNumber,period_count: u32)->DispatchResult{
+            let sender = ensure_signed(origin)?;
+            let token = Self::token_details(id).ok_or(Error::<T>::UnknownToken)?;
+            ensure!(Self::has_role(id,(Role::Admin,sender.clone())),Error::<T>::MissingRole);
+            ensure!(!period.is_zero() && period_count > 0,Error::<T>::InvalidVestingSchedule);
+            let now = <system::Module<T>>::block_number();
+            ensure!(start <= now.saturating_add(T::MaxVestingDelay::get()),Error::<T>::InvalidVestingSchedule);
+            let duration = period.checked_mul(&T::BlockNumber::from(period_count)).ok_or(Error::<T>::InvalidVestingSchedule)?;
+            ensure!(duration <= T::MaxVestingDuration::get(),Error::<T>::InvalidVestingSchedule);
+            ensure!(amount >= token.min_vested_transfer,Error::<T>::VestedTransferTooLow);
+            let mut schedules = Self::vesting(id,&to);
+            ensure!((schedules.len() as u32) < T::MaxVestingSchedules::get(),Error::<T>::TooManyVestingSchedules);
+            Self::_transfer(id,sender,to.clone(),amount)?;
+            let schedule = VestingSchedule {
+                amount,
+                start,
+                period,
+                period_count,
+            };
+            let locked = Self::locked(id,&to).saturating_add(schedule.locked_at(now));
+            schedules.push(schedule);
+            <Locked<T>>::insert(id,&to,locked);
+            <Vesting<T>>::insert(id,&to,schedules);
+            Self::deposit_event(RawEvent::VestingCreated(id,to,amount));
+            Ok(())
+        }
+
+        /// Unlock the part of the caller's vesting schedules that has vested by now, and drop
+        /// the schedules that have fully vested.
+        #[weight=0]
+        fn vest(origin,id: TokenId)->DispatchResult{
+            let sender = ensure_signed(origin)?;
+            let mut schedules = Self::vesting(id,&sender);
+            ensure!(!schedules.is_empty(),Error::<T>::NotVesting);
+            let now = <system::Module<T>>::block_number();
+            schedules.retain(|schedule| !schedule.locked_at(now).is_zero());
+            let locked = schedules.iter().fold(T::TokenBalance::zero(),|locked,schedule| locked.saturating_add(schedule.locked_at(now)));
+            if schedules.is_empty() {
+                <Vesting<T>>::remove(id,&sender);
+                <Locked<T>>::remove(id,&sender);
+                Self::deposit_event(RawEvent::VestingCompleted(id,sender));
+            } else {
+                <Vesting<T>>::insert(id,&sender,schedules);
+                <Locked<T>>::insert(id,&sender,locked);
+                Self::deposit_event(RawEvent::Vested(id,sender,locked));
+            }
+            Ok(())
+        }
+
+        /// Record the balances of token `id` as they are now. Callable by the token's admins
+        /// or `AdminOrigin`.
+        #[weight=0]
+        fn snapshot(origin,id: TokenId)->DispatchResult{
+            Self::ensure_token(id)?;
+            Self::ensure_admin(origin,id)?;
+            Self::take_snapshot(id)?;
+            Ok(())
+        }
+
+        /// Set the smallest amount `vested_transfer` accepts for token `id`. Callable by the
+        /// token's admins or `AdminOrigin`.
+        #[weight=0]
+        fn set_min_vested_transfer(origin,id: TokenId,amount: T::TokenBalance)->DispatchResult{
+            Self::ensure_token(id)?;
+            Self::ensure_admin(origin,id)?;
+            <Tokens<T>>::mutate(id,|token| if let Some(token) = token {
+                token.min_vested_transfer = amount;
+            });
+            Self::deposit_event(RawEvent::MinVestedTransferSet(id,amount));
+            Ok(())
+        }
+
+        /// Stop all transfers and approvals of token `id`.
+        #[weight=0]
+        fn pause(origin,id: TokenId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            PausedTokens::insert(id,true);
+            Self::deposit_event(RawEvent::Paused(id));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn unpause(origin,id: TokenId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            PausedTokens::remove(id);
+            Self::deposit_event(RawEvent::Unpaused(id));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn freeze(origin,id: TokenId,who: T::AccountId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            <FrozenAccounts<T>>::insert(id,&who,true);
+            Self::deposit_event(RawEvent::Frozen(id,who));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn thaw(origin,id: TokenId,who: T::AccountId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            <FrozenAccounts<T>>::remove(id,&who);
+            Self::deposit_event(RawEvent::Thawed(id,who));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn block_account(origin,id: TokenId,who: T::AccountId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            <Blocklist<T>>::insert(id,&who,true);
+            Self::deposit_event(RawEvent::Blocked(id,who));
+            Ok(())
+        }
+
+        #[weight=0]
+        fn unblock_account(origin,id: TokenId,who: T::AccountId)->DispatchResult{
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::ensure_token(id)?;
+            <Blocklist<T>>::remove(id,&who);
+            Self::deposit_event(RawEvent::Unblocked(id,who));
             Ok(())
         }
     }
 }
 
 impl<T: Trait> Module<T> {
-    fn _transfer(from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> DispatchResult {
-        let sender_balance = Self::balance_of(from.clone());
-        ensure!(sender_balance >= value, "Not enough balance.");
+    fn ensure_token(id: TokenId) -> DispatchResult {
+        ensure!(<Tokens<T>>::contains_key(id), Error::<T>::UnknownToken);
+        Ok(())
+    }
+
+    /// The message `owner` has to sign for `permit` on this chain.
+    pub fn permit_message(
+        id: TokenId,
+        owner: &T::AccountId,
+        spender: &T::AccountId,
+        value: &T::TokenBalance,
+        nonce: u64,
+        deadline: &T::BlockNumber,
+    ) -> Result<Vec<u8>, DispatchError> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        let pallet_index = T::ModuleToIndex::module_to_index::<Self>()
+            .ok_or(Error::<T>::UnknownModuleIndex)? as u32;
+        Ok(encode_permit(
+            &genesis_hash,
+            pallet_index,
+            id,
+            owner,
+            spender,
+            value,
+            nonce,
+            deadline,
+        ))
+    }
+
+    /// Check whether `from` could send `amount` of token `id` to `to` right now.
+    pub fn can_transfer(
+        id: TokenId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::TokenBalance,
+    ) -> Result<(), TransferError> {
+        Self::can_move(id, Some(from), Some(to), amount)
+    }
+
+    /// Check whether `amount` of token `id` could leave `from` and reach `to` right now.
+    ///
+    /// Mints have no `from` and burns no `to`, so they pass the same pause, freeze and
+    /// blocklist checks as transfers.
+    fn can_move(
+        id: TokenId,
+        from: Option<&T::AccountId>,
+        to: Option<&T::AccountId>,
+        amount: T::TokenBalance,
+    ) -> Result<(), TransferError> {
+        ensure!(<Tokens<T>>::contains_key(id), TransferError::UnknownToken);
+        ensure!(!Self::is_paused(id), TransferError::Paused);
+        if let Some(from) = from {
+            ensure!(!Self::is_blocked(id, from), TransferError::SenderBlocked);
+        }
+        if let Some(to) = to {
+            ensure!(!Self::is_blocked(id, to), TransferError::RecipientBlocked);
+        }
+        if let Some(from) = from {
+            ensure!(!Self::is_frozen(id, from), TransferError::SenderFrozen);
+            let balance = Self::balance_of(id, from);
+            ensure!(balance >= amount, TransferError::InsufficientBalance);
+            ensure!(
+                balance - amount >= Self::locked(id, from),
+                TransferError::Locked
+            );
+        }
+        if let Some(to) = to {
+            if from != Some(to) {
+                Self::balance_of(id, to)
+                    .checked_add(&amount)
+                    .ok_or(TransferError::Overflow)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a new snapshot of token `id` and return its id.
+    ///
+    /// Nothing is copied here: balances and supply are recorded the first time they change
+    /// afterwards, so taking a snapshot costs the same however many holders there are.
+    pub fn take_snapshot(id: TokenId) -> Result<SnapshotId, DispatchError> {
+        let snapshot = Self::current_snapshot(id)
+            .checked_add(1)
+            .ok_or(Error::<T>::NoAvailableSnapshotId)?;
+        CurrentSnapshot::insert(id, snapshot);
+        Self::deposit_event(RawEvent::Snapshot(id, snapshot));
+        Ok(snapshot)
+    }
+
+    /// Balance of `who` at `snapshot`, or `None` if the snapshot has not been taken.
+    pub fn balance_of_at(
+        id: TokenId,
+        who: &T::AccountId,
+        snapshot: SnapshotId,
+    ) -> Option<T::TokenBalance> {
+        if snapshot == 0 || snapshot > Self::current_snapshot(id) {
+            return None;
+        }
+        let records = <BalanceSnapshots<T>>::get(id, who);
+        Some(Self::value_at(&records, snapshot).unwrap_or_else(|| Self::balance_of(id, who)))
+    }
+
+    /// Total supply at `snapshot`, or `None` if the snapshot has not been taken.
+    pub fn total_supply_at(id: TokenId, snapshot: SnapshotId) -> Option<T::TokenBalance> {
+        if snapshot == 0 || snapshot > Self::current_snapshot(id) {
+            return None;
+        }
+        let records = <SupplySnapshots<T>>::get(id);
+        Self::value_at(&records, snapshot).or_else(|| {
+            Self::token_details(id).map(|token| token.total_supply)
+        })
+    }
+
+    /// The value recorded for `snapshot`, which is the one of the earliest record taken at or
+    /// after it. `None` means the value has not changed since.
+    fn value_at(
+        records: &[(SnapshotId, T::TokenBalance)],
+        snapshot: SnapshotId,
+    ) -> Option<T::TokenBalance> {
+        let index = match records.binary_search_by_key(&snapshot, |(id, _)| *id) {
+            Ok(index) | Err(index) => index,
+        };
+        records.get(index).map(|(_, value)| *value)
+    }
+
+    /// Set `who`'s balance, first recording the old one if this is its first change since
+    /// the latest snapshot.
+    fn set_balance(id: TokenId, who: &T::AccountId, value: T::TokenBalance) {
+        let current = Self::current_snapshot(id);
+        if current > 0 {
+            let old = Self::balance_of(id, who);
+            <BalanceSnapshots<T>>::mutate(id, who, |records| {
+                if records.last().map_or(true, |(last, _)| *last < current) {
+                    records.push((current, old));
+                }
+            });
+        }
+        <Balanceof<T>>::insert(id, who, value);
+    }
+
+    /// Store `token`, first recording the old total supply if this is its first change since
+    /// the latest snapshot.
+    fn set_token(id: TokenId, token: Erc20Token<T::TokenBalance>) {
+        let current = Self::current_snapshot(id);
+        if current > 0 {
+            if let Some(old) = Self::token_details(id) {
+                <SupplySnapshots<T>>::mutate(id, |records| {
+                    if records.last().map_or(true, |(last, _)| *last < current) {
+                        records.push((current, old.total_supply));
+                    }
+                });
+            }
+        }
+        <Tokens<T>>::insert(id, token);
+    }
+
+    fn ensure_can_approve(id: TokenId, owner: &T::AccountId, spender: &T::AccountId) -> DispatchResult {
+        Self::ensure_token(id)?;
+        ensure!(!Self::is_paused(id), Error::<T>::TokenPaused);
+        ensure!(
+            !Self::is_blocked(id, owner) && !Self::is_blocked(id, spender),
+            Error::<T>::AccountBlocked
+        );
+        Ok(())
+    }
+
+    fn ensure_admin(origin: T::Origin, id: TokenId) -> DispatchResult {
+        if let Err(origin) = T::AdminOrigin::try_origin(origin) {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::has_role(id, (Role::Admin, who)), Error::<T>::MissingRole);
+        }
+        Ok(())
+    }
+
+    fn _transfer(
+        id: TokenId,
+        from: T::AccountId,
+        to: T::AccountId,
+        value: T::TokenBalance,
+    ) -> DispatchResult {
+        Self::can_transfer(id, &from, &to, value).map_err(Error::<T>::from)?;
+        let sender_balance = Self::balance_of(id, from.clone());
         let updated_from_balance = sender_balance
             .checked_sub(&value)
+            .ok_or(Error::<T>::InsufficientBalance)?;
+        // A transfer to oneself only has to pass the balance check.
+        if from != to {
+            let receiver_balance = Self::balance_of(id, to.clone());
+            let updated_to_balance = receiver_balance
+                .checked_add(&value)
+                .ok_or(Error::<T>::Storageoverflow)?;
+
+            Self::set_balance(id, &from, updated_from_balance);
+            Self::set_balance(id, &to, updated_to_balance);
+        }
+
+        Self::deposit_event(RawEvent::Transfer(id, from, to, value));
+        Ok(())
+    }
+
+    fn _mint(id: TokenId, to: T::AccountId, amount: T::TokenBalance) -> DispatchResult {
+        Self::can_move(id, None, Some(&to), amount).map_err(Error::<T>::from)?;
+        let mut token = Self::token_details(id).ok_or(Error::<T>::UnknownToken)?;
+        token.total_supply = token
+            .total_supply
+            .checked_add(&amount)
             .ok_or(Error::<T>::Storageoverflow)?;
-        let receiver_balance = Self::balance_of(to.clone());
-        let updated_to_balance = receiver_balance
-            .checked_sub(&value)
+        let updated_balance = Self::balance_of(id, to.clone())
+            .checked_add(&amount)
             .ok_or(Error::<T>::Storageoverflow)?;
 
-        <Balanceof<T>>::insert(from.clone(), updated_from_balance);
-        <Balanceof<T>>::insert(to.clone(), updated_to_balance);
+        Self::set_token(id, token);
+        Self::set_balance(id, &to, updated_balance);
 
-        Self::deposit_event(RawEvent::Transfer(from, to, value));
+        Self::deposit_event(RawEvent::Minted(id, to, amount));
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn _burn(id: TokenId, from: T::AccountId, amount: T::TokenBalance) -> DispatchResult {
+        Self::can_move(id, Some(&from), None, amount).map_err(Error::<T>::from)?;
+        let mut token = Self::token_details(id).ok_or(Error::<T>::UnknownToken)?;
+        let updated_balance = Self::balance_of(id, from.clone())
+            .checked_sub(&amount)
+            .ok_or(Error::<T>::InsufficientBalance)?;
+        token.total_supply = token
+            .total_supply
+            .checked_sub(&amount)
+            .ok_or(Error::<T>::Storageoverflow)?;
+
+        Self::set_token(id, token);
+        Self::set_balance(id, &from, updated_balance);
+
+        Self::deposit_event(RawEvent::Burned(id, from, amount));
+        Ok(())
+    }
+
+    fn _approve(id: TokenId, owner: T::AccountId, spender: T::AccountId, value: T::TokenBalance) {
+        <Allowance<T>>::insert(id, (owner.clone(), spender.clone()), value);
+        Self::deposit_event(RawEvent::Approval(id, owner, spender, value));
+    }
+}
@@ -1,15 +1,27 @@
 use crate::{Module, Trait};
 use sp_core::H256;
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId}, Perbill,
 };
 use frame_system as system;
+use system::EnsureRoot;
 
 impl_outer_origin! {
 	pub enum Origin for Test {}
 }
 
+mod erc20 {
+	pub use crate::Event;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		erc20<T>,
+	}
+}
+
 // Configure a mock runtime to test the pallet.
 
 #[derive(Clone, Eq, PartialEq)]
@@ -19,6 +31,9 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const MaxVestingSchedules: u32 = 3;
+	pub const MaxVestingDelay: u64 = 100;
+	pub const MaxVestingDuration: u64 = 1_000;
 }
 
 impl system::Trait for Test {
@@ -32,7 +47,7 @@ impl system::Trait for Test {
 	type AccountId = u64;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type DbWeight = ();
@@ -50,12 +65,51 @@ impl system::Trait for Test {
 }
 
 impl Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
+	type TokenBalance = u64;
+	type AdminOrigin = EnsureRoot<u64>;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type MaxVestingSchedules = MaxVestingSchedules;
+	type MaxVestingDelay = MaxVestingDelay;
+	type MaxVestingDuration = MaxVestingDuration;
+}
+
+/// A pallet that knows nothing about ERC20 and charges buyers through the `fungibles` traits.
+pub mod shop {
+	use crate::{fungibles::Transfer, TokenId};
+	use frame_support::{decl_module, dispatch::DispatchResult};
+	use frame_system::ensure_signed;
+
+	pub trait Trait: frame_system::Trait {
+		type Assets: Transfer<Self::AccountId, AssetId = TokenId, Balance = u64>;
+	}
+
+	decl_module! {
+		pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+			#[weight = 0]
+			pub fn buy(origin, asset: TokenId, seller: T::AccountId, price: u64) -> DispatchResult {
+				let buyer = ensure_signed(origin)?;
+				T::Assets::transfer(asset, &buyer, &seller, price, false)?;
+				Ok(())
+			}
+		}
+	}
+}
+
+impl shop::Trait for Test {
+	type Assets = ERC20Module;
 }
 
+pub type System = system::Module<Test>;
+pub type Shop = shop::Module<Test>;
 pub type ERC20Module = Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut ext: sp_io::TestExternalities =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	// Events are not recorded on the genesis block.
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
@@ -1,23 +1,801 @@
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{mock::*, Erc20Token, Error, RawEvent, Role, TransferError, VestingSchedule};
+use sp_runtime::testing::TestSignature;
+use frame_support::{assert_noop, assert_ok};
+
+fn token(name: &[u8], ticker: &[u8], total_supply: u64) -> Erc20Token<u64> {
+	Erc20Token { name: name.to_vec(), ticker: ticker.to_vec(), total_supply, min_vested_transfer: 0 }
+}
+
+#[test]
+fn create_token_allocates_ids_and_mints_to_creator() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 100));
+		assert_ok!(ERC20Module::create_token(Origin::signed(2), b"Two".to_vec(), b"TWO".to_vec(), 50));
+
+		assert_eq!(ERC20Module::next_token_id(), 2);
+		assert_eq!(ERC20Module::token_details(0), Some(token(b"One", b"ONE", 100)));
+		assert_eq!(ERC20Module::token_details(1), Some(token(b"Two", b"TWO", 50)));
+		assert_eq!(ERC20Module::balance_of(0, 1), 100);
+		assert_eq!(ERC20Module::balance_of(1, 2), 50);
+		assert_eq!(ERC20Module::balance_of(1, 1), 0);
+	});
+}
+
+#[test]
+fn creating_again_does_not_reinitialize_an_existing_token() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 100));
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 7));
+
+		assert_eq!(ERC20Module::token_details(0), Some(token(b"One", b"ONE", 100)));
+		assert_eq!(ERC20Module::balance_of(0, 1), 100);
+		assert_eq!(ERC20Module::balance_of(1, 1), 7);
+	});
+}
+
+#[test]
+fn create_token_fails_when_ids_are_exhausted() {
+	new_test_ext().execute_with(|| {
+		crate::NextTokenId::put(u32::max_value());
+		assert_noop!(
+			ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 100),
+			Error::<Test>::NoAvailableTokenId
+		);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn unknown_token_is_rejected() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(ERC20Module::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(ERC20Module::something(), Some(42));
+		assert_noop!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10), Error::<Test>::UnknownToken);
+		assert_noop!(ERC20Module::approve(Origin::signed(1), 0, 2, 10), Error::<Test>::UnknownToken);
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(2), 0, 1, 2, 10),
+			Error::<Test>::UnknownToken
+		);
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn approvals_are_kept_per_token() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 100));
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"Two".to_vec(), b"TWO".to_vec(), 100));
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 1, 2, 30));
+
+		assert_eq!(ERC20Module::allowance(1, (1, 2)), 30);
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 0);
+	});
+}
+
+fn new_token_ext() -> sp_io::TestExternalities {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"One".to_vec(), b"ONE".to_vec(), 100));
+	});
+	ext
+}
+
+fn last_event() -> TestEvent {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn transfer_moves_balance_and_emits_transfer() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 40));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 60);
+		assert_eq!(ERC20Module::balance_of(0, 2), 40);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Transfer(0, 1, 2, 40)));
+	});
+}
+
+#[test]
+fn transfer_more_than_balance_fails() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(ERC20Module::transfer(Origin::signed(1), 0, 2, 101), Error::<Test>::InsufficientBalance);
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 1, 1), Error::<Test>::InsufficientBalance);
+	});
+}
+
+#[test]
+fn zero_and_self_transfers_keep_balances() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 0));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 1, 100));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 100);
+		assert_eq!(ERC20Module::balance_of(0, 2), 0);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Transfer(0, 1, 1, 100)));
+	});
+}
+
+#[test]
+fn transfer_fails_when_receiver_would_overflow() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"Max".to_vec(), b"MAX".to_vec(), u64::max_value()));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 1));
+		crate::Balanceof::<Test>::insert(0, 3, u64::max_value());
+
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 3, 1), Error::<Test>::Storageoverflow);
+	});
+}
+
+#[test]
+fn approve_replaces_the_allowance() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 30));
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 10));
+
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 10);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Approval(0, 1, 2, 10)));
+	});
+}
+
+#[test]
+fn transfer_from_spends_the_callers_allowance() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 30));
+		assert_ok!(ERC20Module::transfer_from(Origin::signed(2), 0, 1, 3, 20));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 80);
+		assert_eq!(ERC20Module::balance_of(0, 3), 20);
+		assert_eq!(ERC20Module::balance_of(0, 2), 0);
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 10);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Approval(0, 1, 2, 10)));
+	});
+}
+
+#[test]
+fn transfer_from_requires_the_signer_to_be_the_spender() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 30));
+
+		// Neither the recipient nor an unrelated account can use 2's allowance.
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(3), 0, 1, 3, 10),
+			Error::<Test>::InsufficientAllowance
+		);
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(4), 0, 1, 2, 10),
+			Error::<Test>::InsufficientAllowance
+		);
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(2), 0, 1, 2, 31),
+			Error::<Test>::InsufficientAllowance
+		);
+	});
+}
+
+#[test]
+fn transfer_from_keeps_allowance_when_balance_is_short() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 500));
+
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(2), 0, 1, 2, 101),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 500);
+	});
+}
+
+#[test]
+fn increase_and_decrease_allowance() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::increase_allowance(Origin::signed(1), 0, 2, 30));
+		assert_ok!(ERC20Module::increase_allowance(Origin::signed(1), 0, 2, 15));
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 45);
+
+		assert_ok!(ERC20Module::decrease_allowance(Origin::signed(1), 0, 2, 40));
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 5);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Approval(0, 1, 2, 5)));
+
+		assert_noop!(
+			ERC20Module::decrease_allowance(Origin::signed(1), 0, 2, 6),
+			Error::<Test>::InsufficientAllowance
+		);
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, u64::max_value()));
+		assert_noop!(
+			ERC20Module::increase_allowance(Origin::signed(1), 0, 2, 1),
+			Error::<Test>::Storageoverflow
+		);
+	});
+}
+
+#[test]
+fn creator_is_admin_and_can_grant_roles() {
+	new_token_ext().execute_with(|| {
+		assert!(ERC20Module::has_role(0, (Role::Admin, 1)));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 2));
+		assert!(ERC20Module::has_role(0, (Role::Minter, 2)));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::RoleGranted(0, Role::Minter, 2)));
+
+		assert_noop!(
+			ERC20Module::grant_role(Origin::signed(2), 0, Role::Minter, 3),
+			Error::<Test>::MissingRole
+		);
+		assert_ok!(ERC20Module::revoke_role(Origin::signed(1), 0, Role::Minter, 2));
+		assert!(!ERC20Module::has_role(0, (Role::Minter, 2)));
+	});
+}
+
+#[test]
+fn admin_origin_manages_roles_of_any_token() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::root(), 0, Role::Burner, 3));
+		assert!(ERC20Module::has_role(0, (Role::Burner, 3)));
+		assert_ok!(ERC20Module::revoke_role(Origin::root(), 0, Role::Admin, 1));
+		assert_noop!(
+			ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1),
+			Error::<Test>::MissingRole
+		);
+	});
+}
+
+#[test]
+fn mint_requires_minter_and_grows_supply() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(ERC20Module::mint(Origin::signed(1), 0, 2, 50), Error::<Test>::MissingRole);
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1));
+		assert_ok!(ERC20Module::mint(Origin::signed(1), 0, 2, 50));
+
+		assert_eq!(ERC20Module::balance_of(0, 2), 50);
+		assert_eq!(ERC20Module::token_details(0), Some(token(b"One", b"ONE", 150)));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Minted(0, 2, 50)));
+
+		assert_noop!(
+			ERC20Module::mint(Origin::signed(1), 0, 2, u64::max_value()),
+			Error::<Test>::Storageoverflow
+		);
+	});
+}
+
+#[test]
+fn burn_requires_burner_and_shrinks_supply() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(ERC20Module::burn(Origin::signed(1), 0, 10), Error::<Test>::MissingRole);
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 1));
+		assert_ok!(ERC20Module::burn(Origin::signed(1), 0, 10));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 90);
+		assert_eq!(ERC20Module::token_details(0), Some(token(b"One", b"ONE", 90)));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Burned(0, 1, 10)));
+
+		assert_noop!(ERC20Module::burn(Origin::signed(1), 0, 91), Error::<Test>::InsufficientBalance);
+	});
+}
+
+#[test]
+fn burn_from_spends_allowance() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 2));
 		assert_noop!(
-			ERC20Module::cause_error(Origin::signed(1)),
-			Error::<Test>::NoneValue
+			ERC20Module::burn_from(Origin::signed(2), 0, 1, 10),
+			Error::<Test>::InsufficientAllowance
 		);
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 30));
+		assert_ok!(ERC20Module::burn_from(Origin::signed(2), 0, 1, 10));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 90);
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 20);
+		assert_eq!(ERC20Module::token_details(0), Some(token(b"One", b"ONE", 90)));
+
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 3, 30));
+		assert_noop!(ERC20Module::burn_from(Origin::signed(3), 0, 1, 10), Error::<Test>::MissingRole);
+	});
+}
+
+#[test]
+fn another_pallet_moves_tokens_through_fungibles() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(Shop::buy(Origin::signed(1), 0, 2, 25));
+
+		assert_eq!(ERC20Module::balance_of(0, 1), 75);
+		assert_eq!(ERC20Module::balance_of(0, 2), 25);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Transfer(0, 1, 2, 25)));
+
+		assert_noop!(Shop::buy(Origin::signed(2), 0, 1, 26), Error::<Test>::InsufficientBalance);
+		assert_noop!(Shop::buy(Origin::signed(1), 1, 2, 1), Error::<Test>::UnknownToken);
+	});
+}
+
+#[test]
+fn fungibles_mint_and_burn_track_issuance() {
+	use crate::fungibles::{Inspect, Mutate};
+
+	new_token_ext().execute_with(|| {
+		assert_ok!(<ERC20Module as Mutate<u64>>::mint_into(0, &2, 40));
+		assert_eq!(<ERC20Module as Mutate<u64>>::burn_from(0, &1, 30), Ok(30));
+
+		assert_eq!(<ERC20Module as Inspect<u64>>::total_issuance(0), 110);
+		assert_eq!(<ERC20Module as Inspect<u64>>::balance(0, &2), 40);
+		assert_eq!(<ERC20Module as Inspect<u64>>::reducible_balance(0, &1, false), 70);
+		assert_eq!(<ERC20Module as Inspect<u64>>::total_issuance(1), 0);
+	});
+}
+
+frame_support::parameter_types! {
+	pub const FirstToken: crate::TokenId = 0;
+}
+
+#[test]
+fn item_of_exposes_one_token_as_a_currency() {
+	use crate::fungible::{Inspect, ItemOf, Transfer};
+	type One = ItemOf<ERC20Module, FirstToken, u64>;
+
+	new_token_ext().execute_with(|| {
+		assert_eq!(One::total_issuance(), 100);
+		assert_eq!(One::transfer(&1, &3, 10, false), Ok(10));
+		assert_eq!(One::balance(&3), 10);
+		assert_eq!(ERC20Module::balance_of(0, 3), 10);
+	});
+}
+
+fn sign_permit(owner: u64, spender: u64, value: u64, deadline: u64) -> TestSignature {
+	let nonce = ERC20Module::permit_nonce(owner);
+	TestSignature(owner, ERC20Module::permit_message(0, &owner, &spender, &value, nonce, &deadline).unwrap())
+}
+
+#[test]
+fn permit_sets_allowance_from_the_owners_signature() {
+	new_token_ext().execute_with(|| {
+		let signature = sign_permit(1, 2, 30, 10);
+		// Account 5 relays the permit and pays for it.
+		assert_ok!(ERC20Module::permit(Origin::signed(5), 0, 1, 2, 30, 10, signature.clone()));
+
+		assert_eq!(ERC20Module::allowance(0, (1, 2)), 30);
+		assert_eq!(ERC20Module::permit_nonce(1), 1);
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Approval(0, 1, 2, 30)));
+
+		// The nonce moved on, so the same signature cannot be replayed.
+		assert_noop!(
+			ERC20Module::permit(Origin::signed(5), 0, 1, 2, 30, 10, signature),
+			Error::<Test>::BadSignature
+		);
+	});
+}
+
+#[test]
+fn permit_rejects_other_signers_and_altered_terms() {
+	new_token_ext().execute_with(|| {
+		let signature = sign_permit(1, 2, 30, 10);
+		assert_noop!(
+			ERC20Module::permit(Origin::signed(5), 0, 1, 2, 31, 10, signature.clone()),
+			Error::<Test>::BadSignature
+		);
+		assert_noop!(
+			ERC20Module::permit(Origin::signed(5), 0, 1, 3, 30, 10, signature),
+			Error::<Test>::BadSignature
+		);
+
+		let forged = TestSignature(2, ERC20Module::permit_message(0, &1, &2, &30, 0, &10).unwrap());
+		assert_noop!(
+			ERC20Module::permit(Origin::signed(5), 0, 1, 2, 30, 10, forged),
+			Error::<Test>::BadSignature
+		);
+	});
+}
+
+#[test]
+fn permit_expires_after_its_deadline() {
+	new_token_ext().execute_with(|| {
+		let signature = sign_permit(1, 2, 30, 10);
+		System::set_block_number(11);
+		assert_noop!(
+			ERC20Module::permit(Origin::signed(5), 0, 1, 2, 30, 10, signature),
+			Error::<Test>::PermitExpired
+		);
+	});
+}
+
+#[test]
+fn encode_permit_matches_the_on_chain_message() {
+	new_token_ext().execute_with(|| {
+		let genesis_hash = System::block_hash(0);
+		assert_eq!(
+			crate::encode_permit(&genesis_hash, 0, 0, &1u64, &2u64, &30u64, 0, &10u64),
+			ERC20Module::permit_message(0, &1, &2, &30, 0, &10).unwrap()
+		);
+	});
+}
+
+#[test]
+fn controls_require_admin_origin() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(ERC20Module::pause(Origin::signed(1), 0), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(ERC20Module::freeze(Origin::signed(1), 0, 2), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(ERC20Module::block_account(Origin::signed(1), 0, 2), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(ERC20Module::pause(Origin::root(), 1), Error::<Test>::UnknownToken);
+	});
+}
+
+#[test]
+fn pause_blocks_transfers_and_approvals() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 50));
+		assert_ok!(ERC20Module::pause(Origin::root(), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Paused(0)));
+
+		assert_noop!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10), Error::<Test>::TokenPaused);
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(2), 0, 1, 3, 10),
+			Error::<Test>::TokenPaused
+		);
+		assert_noop!(ERC20Module::approve(Origin::signed(1), 0, 3, 10), Error::<Test>::TokenPaused);
+		assert_noop!(
+			ERC20Module::increase_allowance(Origin::signed(1), 0, 2, 10),
+			Error::<Test>::TokenPaused
+		);
+		assert_eq!(ERC20Module::can_transfer(0, &1, &2, 10), Err(TransferError::Paused));
+
+		assert_ok!(ERC20Module::unpause(Origin::root(), 0));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10));
+	});
+}
+
+#[test]
+fn pause_blocks_mints_and_burns() {
+	use crate::fungibles::Mutate;
+
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 2));
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 50));
+		assert_ok!(ERC20Module::pause(Origin::root(), 0));
+
+		assert_noop!(ERC20Module::mint(Origin::signed(1), 0, 2, 10), Error::<Test>::TokenPaused);
+		assert_noop!(ERC20Module::burn(Origin::signed(1), 0, 10), Error::<Test>::TokenPaused);
+		assert_noop!(
+			ERC20Module::burn_from(Origin::signed(2), 0, 1, 10),
+			Error::<Test>::TokenPaused
+		);
+		assert_noop!(<ERC20Module as Mutate<u64>>::mint_into(0, &2, 10), Error::<Test>::TokenPaused);
+		assert_noop!(<ERC20Module as Mutate<u64>>::burn_from(0, &1, 10), Error::<Test>::TokenPaused);
+
+		assert_ok!(ERC20Module::unpause(Origin::root(), 0));
+		assert_ok!(ERC20Module::mint(Origin::signed(1), 0, 2, 10));
+		assert_ok!(ERC20Module::burn(Origin::signed(1), 0, 10));
+	});
+}
+
+#[test]
+fn frozen_accounts_can_receive_but_not_send() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::freeze(Origin::root(), 0, 1));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Frozen(0, 1)));
+
+		assert_noop!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10), Error::<Test>::AccountFrozen);
+		assert_eq!(ERC20Module::can_transfer(0, &1, &2, 10), Err(TransferError::SenderFrozen));
+
+		assert_ok!(ERC20Module::thaw(Origin::root(), 0, 1));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10));
+		assert_ok!(ERC20Module::freeze(Origin::root(), 0, 1));
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 1, 5));
+		assert_eq!(ERC20Module::balance_of(0, 1), 95);
+	});
+}
+
+#[test]
+fn frozen_accounts_can_be_minted_to_but_not_burned_from() {
+	use crate::fungibles::Mutate;
+
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 2));
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 50));
+		assert_ok!(ERC20Module::freeze(Origin::root(), 0, 1));
+
+		assert_noop!(ERC20Module::burn(Origin::signed(1), 0, 10), Error::<Test>::AccountFrozen);
+		assert_noop!(
+			ERC20Module::burn_from(Origin::signed(2), 0, 1, 10),
+			Error::<Test>::AccountFrozen
+		);
+		assert_noop!(<ERC20Module as Mutate<u64>>::burn_from(0, &1, 10), Error::<Test>::AccountFrozen);
+
+		assert_ok!(ERC20Module::mint(Origin::signed(1), 0, 1, 10));
+		assert_ok!(<ERC20Module as Mutate<u64>>::mint_into(0, &1, 10));
+		assert_eq!(ERC20Module::balance_of(0, 1), 120);
+	});
+}
+
+#[test]
+fn blocked_accounts_cannot_be_minted_to_or_burned_from() {
+	use crate::fungibles::Mutate;
+
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 2));
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 2, 50));
+		assert_ok!(ERC20Module::block_account(Origin::root(), 0, 3));
+
+		assert_noop!(ERC20Module::mint(Origin::signed(1), 0, 3, 10), Error::<Test>::AccountBlocked);
+		assert_noop!(<ERC20Module as Mutate<u64>>::mint_into(0, &3, 10), Error::<Test>::AccountBlocked);
+
+		// A blocked holder cannot burn, nor a blocked spender burn for someone else.
+		assert_ok!(ERC20Module::block_account(Origin::root(), 0, 1));
+		assert_noop!(ERC20Module::burn(Origin::signed(1), 0, 10), Error::<Test>::AccountBlocked);
+		assert_noop!(
+			ERC20Module::burn_from(Origin::signed(2), 0, 1, 10),
+			Error::<Test>::AccountBlocked
+		);
+		assert_noop!(<ERC20Module as Mutate<u64>>::burn_from(0, &1, 10), Error::<Test>::AccountBlocked);
+
+		assert_ok!(ERC20Module::unblock_account(Origin::root(), 0, 1));
+		assert_ok!(ERC20Module::block_account(Origin::root(), 0, 2));
+		assert_noop!(
+			ERC20Module::burn_from(Origin::signed(2), 0, 1, 10),
+			Error::<Test>::AccountBlocked
+		);
+		assert_ok!(ERC20Module::burn(Origin::signed(1), 0, 10));
+	});
+}
+
+#[test]
+fn blocked_accounts_cannot_send_receive_or_spend() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::approve(Origin::signed(1), 0, 3, 50));
+		assert_ok!(ERC20Module::block_account(Origin::root(), 0, 3));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Blocked(0, 3)));
+
+		assert_noop!(ERC20Module::transfer(Origin::signed(1), 0, 3, 10), Error::<Test>::AccountBlocked);
+		assert_noop!(
+			ERC20Module::transfer_from(Origin::signed(3), 0, 1, 2, 10),
+			Error::<Test>::AccountBlocked
+		);
+		assert_noop!(ERC20Module::approve(Origin::signed(1), 0, 3, 10), Error::<Test>::AccountBlocked);
+		assert_eq!(ERC20Module::can_transfer(0, &1, &3, 10), Err(TransferError::RecipientBlocked));
+		assert_eq!(ERC20Module::can_transfer(0, &3, &1, 0), Err(TransferError::SenderBlocked));
+
+		assert_ok!(ERC20Module::unblock_account(Origin::root(), 0, 3));
+		assert_ok!(ERC20Module::transfer_from(Origin::signed(3), 0, 1, 2, 10));
+	});
+}
+
+#[test]
+fn can_transfer_reports_balance_problems() {
+	new_token_ext().execute_with(|| {
+		assert_eq!(ERC20Module::can_transfer(0, &1, &2, 100), Ok(()));
+		assert_eq!(ERC20Module::can_transfer(0, &1, &2, 101), Err(TransferError::InsufficientBalance));
+		assert_eq!(ERC20Module::can_transfer(1, &1, &2, 1), Err(TransferError::UnknownToken));
+	});
+}
+
+#[test]
+fn snapshot_ids_increase_and_require_an_admin() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(ERC20Module::snapshot(Origin::signed(2), 0), Error::<Test>::MissingRole);
+		assert_ok!(ERC20Module::snapshot(Origin::signed(1), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Snapshot(0, 1)));
+		assert_ok!(ERC20Module::snapshot(Origin::root(), 0));
+		assert_eq!(ERC20Module::current_snapshot(0), 2);
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(3));
+	});
+}
+
+#[test]
+fn balances_are_recorded_at_their_first_change_after_a_snapshot() {
+	new_token_ext().execute_with(|| {
+		assert_eq!(ERC20Module::balance_of_at(0, &1, 1), None);
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(1));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 30));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 30));
+
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(2));
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(3));
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 3, 10));
+
+		assert_eq!(ERC20Module::balance_of_at(0, &1, 1), Some(100));
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 1), Some(0));
+		assert_eq!(ERC20Module::balance_of_at(0, &1, 2), Some(40));
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 2), Some(60));
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 3), Some(60));
+		assert_eq!(ERC20Module::balance_of_at(0, &3, 3), Some(0));
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 4), None);
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 0), None);
+		assert_eq!(ERC20Module::balance_of(0, 2), 50);
+	});
+}
+
+#[test]
+fn total_supply_follows_mint_and_burn_across_snapshots() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Minter, 1));
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 1));
+
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(1));
+		assert_ok!(ERC20Module::mint(Origin::signed(1), 0, 2, 50));
+		assert_eq!(ERC20Module::take_snapshot(0), Ok(2));
+		assert_ok!(ERC20Module::burn(Origin::signed(1), 0, 20));
+
+		assert_eq!(ERC20Module::total_supply_at(0, 1), Some(100));
+		assert_eq!(ERC20Module::total_supply_at(0, 2), Some(150));
+		assert_eq!(ERC20Module::total_supply_at(0, 3), None);
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 1), Some(0));
+		assert_eq!(ERC20Module::balance_of_at(0, &2, 2), Some(50));
+		assert_eq!(ERC20Module::balance_of_at(0, &1, 2), Some(100));
+		assert_eq!(ERC20Module::balance_of(0, 1), 80);
+	});
+}
+
+#[test]
+fn snapshots_are_kept_per_token() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::create_token(Origin::signed(1), b"Two".to_vec(), b"TWO".to_vec(), 100));
+		assert_eq!(ERC20Module::take_snapshot(1), Ok(1));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 10));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 1, 2, 10));
+
+		assert_eq!(ERC20Module::balance_of_at(0, &1, 1), None);
+		assert_eq!(ERC20Module::balance_of_at(1, &1, 1), Some(100));
+	});
+}
+
+#[test]
+fn vesting_schedule_unlocks_in_equal_parts() {
+	let schedule = VestingSchedule { amount: 100u64, start: 10u64, period: 5, period_count: 3 };
+	assert_eq!(schedule.locked_at(0), 100);
+	assert_eq!(schedule.locked_at(14), 100);
+	assert_eq!(schedule.locked_at(15), 67);
+	assert_eq!(schedule.locked_at(19), 67);
+	assert_eq!(schedule.locked_at(20), 34);
+	assert_eq!(schedule.locked_at(25), 0);
+}
+
+#[test]
+fn vested_transfer_locks_the_amount_until_vest() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 60, 10, 10, 3));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::VestingCreated(0, 2, 60)));
+		assert_eq!(ERC20Module::balance_of(0, 2), 60);
+		assert_eq!(ERC20Module::locked(0, 2), 60);
+		assert_eq!(
+			ERC20Module::vesting(0, 2),
+			vec![VestingSchedule { amount: 60, start: 10, period: 10, period_count: 3 }]
+		);
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 3, 1), Error::<Test>::BalanceLocked);
+		assert_eq!(ERC20Module::can_transfer(0, &2, &3, 1), Err(TransferError::Locked));
+
+		System::set_block_number(20);
+		// Nothing moves until the vested part is claimed.
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 3, 1), Error::<Test>::BalanceLocked);
+		assert_ok!(ERC20Module::vest(Origin::signed(2), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Vested(0, 2, 40)));
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 3, 20));
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 3, 1), Error::<Test>::BalanceLocked);
+
+		System::set_block_number(40);
+		assert_ok!(ERC20Module::vest(Origin::signed(2), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::VestingCompleted(0, 2)));
+		assert!(ERC20Module::vesting(0, 2).is_empty());
+		assert_eq!(ERC20Module::locked(0, 2), 0);
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 3, 40));
+		assert_noop!(ERC20Module::vest(Origin::signed(2), 0), Error::<Test>::NotVesting);
+	});
+}
+
+#[test]
+fn tokens_received_on_top_of_a_lock_stay_free() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 10, 3));
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 2, 20));
+
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 3, 20));
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 3, 1), Error::<Test>::BalanceLocked);
+		assert_eq!(<ERC20Module as crate::fungibles::Inspect<u64>>::reducible_balance(0, &2, false), 0);
+	});
+}
+
+#[test]
+fn locked_tokens_cannot_be_burned() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::grant_role(Origin::signed(1), 0, Role::Burner, 2));
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 10, 3));
+		assert_noop!(ERC20Module::burn(Origin::signed(2), 0, 1), Error::<Test>::BalanceLocked);
+	});
+}
+
+#[test]
+fn vested_transfer_checks_the_schedule() {
+	new_token_ext().execute_with(|| {
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 0, 3),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 10, 0),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		// Schedules start within `MaxVestingDelay` blocks and last at most `MaxVestingDuration`.
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 102, 10, 3),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 501, 2),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, u64::max_value(), 2),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 101, 10, 10, 3),
+			Error::<Test>::InsufficientBalance
+		);
+
+		// Each token has its own minimum, set by its admins.
+		assert_noop!(
+			ERC20Module::set_min_vested_transfer(Origin::signed(2), 0, 5),
+			Error::<Test>::MissingRole
+		);
+		assert_ok!(ERC20Module::set_min_vested_transfer(Origin::signed(1), 0, 5));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::MinVestedTransferSet(0, 5)));
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 4, 10, 10, 3),
+			Error::<Test>::VestedTransferTooLow
+		);
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, 101, 500, 2));
+	});
+}
+
+#[test]
+fn holders_cannot_fill_an_account_with_schedules() {
+	new_token_ext().execute_with(|| {
+		assert_ok!(ERC20Module::transfer(Origin::signed(1), 0, 3, 50));
+
+		// Only the token's admins can vest to an account.
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(3), 0, 2, 5, 0, 10, 1),
+			Error::<Test>::MissingRole
+		);
+
+		// And not even they can lock tokens for good.
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, 0, u64::max_value(), 1),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, u64::max_value(), 10, 1),
+			Error::<Test>::InvalidVestingSchedule
+		);
+		assert!(ERC20Module::vesting(0, 2).is_empty());
+	});
+}
+
+#[test]
+fn vesting_schedules_stack_up_to_the_limit() {
+	new_token_ext().execute_with(|| {
+		let slow = VestingSchedule { amount: 5, start: 0, period: 1_000, period_count: 1 };
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, 0, 1_000, 1));
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 10, 10, 3));
+		assert_eq!(ERC20Module::vesting(0, 2).len(), 2);
+		assert_eq!(ERC20Module::locked(0, 2), 35);
+
+		// Vesting sums what the schedules still lock and drops those that are done.
+		System::set_block_number(40);
+		assert_ok!(ERC20Module::vest(Origin::signed(2), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::Vested(0, 2, 5)));
+		assert_eq!(ERC20Module::vesting(0, 2), vec![slow.clone()]);
+		assert_ok!(ERC20Module::transfer(Origin::signed(2), 0, 1, 30));
+		assert_noop!(ERC20Module::transfer(Origin::signed(2), 0, 1, 1), Error::<Test>::BalanceLocked);
+
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, 0, 1_000, 1));
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 5, 0, 1_000, 1));
+		assert_noop!(
+			ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 50, 10, 3),
+			Error::<Test>::TooManyVestingSchedules
+		);
+		assert_eq!(ERC20Module::locked(0, 2), 15);
+
+		// Every schedule ends within `MaxVestingDuration`, freeing its slot.
+		System::set_block_number(1_000);
+		assert_ok!(ERC20Module::vest(Origin::signed(2), 0));
+		assert_eq!(last_event(), TestEvent::erc20(RawEvent::VestingCompleted(0, 2)));
+		assert_ok!(ERC20Module::vested_transfer(Origin::signed(1), 0, 2, 30, 1_010, 10, 3));
 	});
 }
@@ -262,8 +262,21 @@ impl sudo::Trait for Runtime {
 impl template::Trait for Runtime {
 	type Event = Event;
 }
+parameter_types! {
+	pub const MaxVestingSchedules: u32 = 28;
+	pub const MaxVestingDelay: BlockNumber = 365 * DAYS;
+	pub const MaxVestingDuration: BlockNumber = 4 * 365 * DAYS;
+}
+
 impl erc20::Trait for Runtime {
 	type Event = Event;
+	type TokenBalance = Balance;
+	type AdminOrigin = system::EnsureRoot<AccountId>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type MaxVestingSchedules = MaxVestingSchedules;
+	type MaxVestingDelay = MaxVestingDelay;
+	type MaxVestingDuration = MaxVestingDuration;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -397,6 +410,29 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl erc20_runtime_api::Erc20Api<Block, AccountId, Balance> for Runtime {
+		fn can_transfer(
+			id: erc20::TokenId,
+			from: AccountId,
+			to: AccountId,
+			amount: Balance,
+		) -> Result<(), erc20::TransferError> {
+			ERC20Module::can_transfer(id, &from, &to, amount)
+		}
+
+		fn balance_of_at(
+			id: erc20::TokenId,
+			who: AccountId,
+			snapshot: erc20::SnapshotId,
+		) -> Option<Balance> {
+			ERC20Module::balance_of_at(id, &who, snapshot)
+		}
+
+		fn total_supply_at(id: erc20::TokenId, snapshot: erc20::SnapshotId) -> Option<Balance> {
+			ERC20Module::total_supply_at(id, snapshot)
+		}
+	}
+
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
 		fn grandpa_authorities() -> GrandpaAuthorityList {
 			Grandpa::grandpa_authorities()
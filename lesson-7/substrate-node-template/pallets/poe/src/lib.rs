@@ -4,11 +4,11 @@
 
 use frame_support::{
 	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,
-	traits::{Get},
+	traits::{Get, Currency, ReservableCurrency, BalanceStatus},
 };
 use frame_system::{self as system, ensure_signed};
 use sp_std::prelude::*;
-use sp_runtime::traits::StaticLookup;
+use sp_runtime::traits::{StaticLookup, Saturating};
 
 #[cfg(test)]
 mod mock;
@@ -16,6 +16,8 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
 /// The pallet's configuration trait.
 pub trait Trait: system::Trait {
 	// Add other types and constants required to configure this pallet.
@@ -24,6 +26,15 @@ pub trait Trait: system::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
 	type MaxClaimLength: Get<u32>;
+
+	/// The currency in which claim deposits are reserved.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The base deposit reserved for every claim.
+	type DepositBase: Get<BalanceOf<Self>>;
+
+	/// The additional deposit reserved per byte of claim.
+	type DepositPerByte: Get<BalanceOf<Self>>;
 }
 
 // This pallet's storage items.
@@ -33,6 +44,8 @@ decl_storage! {
 	// ---------------------------------vvvvvvvvvvvvvv
 	trait Store for Module<T: Trait> as TemplateModule {
 		Proofs get(fn proofs): map hasher(blake2_128_concat) Vec<u8> => (T::AccountId, T::BlockNumber);
+		/// The deposit reserved from the owner of each claim.
+		ClaimDeposits get(fn claim_deposits): map hasher(blake2_128_concat) Vec<u8> => BalanceOf<T>;
 	}
 }
 
@@ -68,7 +81,15 @@ decl_module! {
 		// this is needed only if you are using events in your pallet
 		fn deposit_event() = default;
 
-		#[weight = 0]
+		/// The base deposit reserved for every claim.
+		const DepositBase: BalanceOf<T> = T::DepositBase::get();
+
+		/// The additional deposit reserved per byte of claim.
+		const DepositPerByte: BalanceOf<T> = T::DepositPerByte::get();
+
+		// The calls are not benchmarked: they are weighed by their storage accesses only,
+		// and the claim bytes are paid for by the transaction length fee.
+		#[weight = T::DbWeight::get().reads(2) + T::DbWeight::get().writes(3)]
 		pub fn create_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
 
@@ -76,6 +97,10 @@ decl_module! {
 
 			ensure!(T::MaxClaimLength::get() >= claim.len() as u32, Error::<T>::ProofTooLong);
 
+			let deposit = Self::deposit_for(claim.len());
+			T::Currency::reserve(&sender, deposit)?;
+			ClaimDeposits::<T>::insert(&claim, deposit);
+
 			Proofs::<T>::insert(&claim, (sender.clone(), system::Module::<T>::block_number()));
 
 			Self::deposit_event(RawEvent::ClaimCreated(sender, claim));
@@ -83,7 +108,7 @@ decl_module! {
 			Ok(())
 		}
 
-		#[weight = 0]
+		#[weight = T::DbWeight::get().reads(3) + T::DbWeight::get().writes(3)]
 		pub fn revoke_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
 
@@ -95,12 +120,14 @@ decl_module! {
 
 			Proofs::<T>::remove(&claim);
 
+			T::Currency::unreserve(&sender, ClaimDeposits::<T>::take(&claim));
+
 			Self::deposit_event(RawEvent::ClaimRevoked(sender, claim));
 
 			Ok(())
 		}
 
-		#[weight = 0]
+		#[weight = T::DbWeight::get().reads(4) + T::DbWeight::get().writes(3)]
 		pub fn transfer_claim(origin, claim: Vec<u8>, dest: <T::Lookup as StaticLookup>::Source) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
 
@@ -112,6 +139,10 @@ decl_module! {
 
 			let dest = T::Lookup::lookup(dest)?;
 
+			if dest != sender {
+				T::Currency::repatriate_reserved(&sender, &dest, Self::claim_deposits(&claim), BalanceStatus::Reserved)?;
+			}
+
 			Proofs::<T>::insert(&claim, (&dest, system::Module::<T>::block_number()));
 
 			Self::deposit_event(RawEvent::ClaimTrans(sender, dest, claim));
@@ -120,3 +151,10 @@ decl_module! {
 		}
 	}
 }
+
+impl<T: Trait> Module<T> {
+	/// The deposit for a claim of `len` bytes.
+	pub fn deposit_for(len: usize) -> BalanceOf<T> {
+		T::DepositBase::get().saturating_add(T::DepositPerByte::get().saturating_mul((len as u32).into()))
+	}
+}
@@ -111,4 +111,51 @@ fn transfer_claim_failed_with_wrong_owner(){
       Error::<Test>::NotClaimOwner
     );
   })
-}
\ No newline at end of file
+}
+
+#[test]
+fn create_claim_reserves_deposit(){
+  new_test_ext().execute_with(||{
+    let claim = vec![0, 1];
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+    // 10 base plus 1 per byte of the two byte claim.
+    assert_eq!(Balances::reserved_balance(1), 12);
+    assert_eq!(PoeModule::claim_deposits(&claim), 12);
+  })
+}
+
+#[test]
+fn create_claim_failed_without_deposit(){
+  new_test_ext().execute_with(||{
+    let claim = vec![0, 1];
+
+    assert!(PoeModule::create_claim(Origin::signed(3), claim.clone()).is_err());
+    assert!(!Proofs::<Test>::contains_key(&claim));
+  })
+}
+
+#[test]
+fn revoke_claim_unreserves_deposit(){
+  new_test_ext().execute_with(||{
+    let claim = vec![0, 1];
+    let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::free_balance(1), 100);
+  })
+}
+
+#[test]
+fn transfer_claim_moves_deposit(){
+  new_test_ext().execute_with(||{
+    let claim = vec![0, 1];
+    let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::free_balance(1), 88);
+    assert_eq!(Balances::reserved_balance(2), 12);
+  })
+}
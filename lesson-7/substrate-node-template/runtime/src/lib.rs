@@ -259,12 +259,18 @@ impl template::Trait for Runtime {
 
 parameter_types! {
 	pub const MaxClaimLength: u32 = 256;
+	pub const ClaimDepositBase: Balance = 10_000;
+	pub const ClaimDepositPerByte: Balance = 100;
 }
 
 impl poe::Trait for Runtime {
 	type Event = Event;
 
 	type MaxClaimLength = MaxClaimLength;
+
+	type Currency = balances::Module<Runtime>;
+	type DepositBase = ClaimDepositBase;
+	type DepositPerByte = ClaimDepositPerByte;
 }
 
 construct_runtime!(
@@ -1,7 +1,7 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	AccountId, AuraConfig, BalancesConfig, BlockNumber, GenesisConfig, GrandpaConfig,
+	PoeModuleConfig, SudoConfig, SystemConfig, WASM_BINARY, Signature
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -62,6 +62,10 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 			],
+			// Pre-seeded claims
+			vec![
+				(b"Hello, Substrate!".to_vec(), get_account_id_from_seed::<sr25519::Public>("Alice"), 0),
+			],
 			true,
 		),
 		// Bootnodes
@@ -110,6 +114,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 			],
+			// Pre-seeded claims
+			vec![],
 			true,
 		),
 		// Bootnodes
@@ -131,6 +137,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	claims: Vec<(Vec<u8>, AccountId, BlockNumber)>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -153,5 +160,10 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		}),
+		pallet_poe: Some(PoeModuleConfig {
+			// Claims exported from another registry can be added to the `claims` of
+			// `palletPoe` in the JSON from `build-spec`.
+			claims,
+		}),
 	}
 }
@@ -0,0 +1,156 @@
+//! RPC interface for the proof of existence pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sc_client_api::ProofProvider;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_poe_runtime_api::PoeApi as PoeRuntimeApi;
+use pallet_poe_runtime_api::CustodyRecord;
+pub use pallet_poe_verifier::ClaimProof;
+
+/// The most claims returned by a single `poe_claimsOf` call.
+pub const MAX_CLAIMS_PER_PAGE: u32 = 1_000;
+
+/// Error code for failed runtime API calls.
+const RUNTIME_ERROR: i64 = 1;
+
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber> {
+	/// The owner and block of the claim on the digest `hash`, if there is one.
+	#[rpc(name = "poe_claim")]
+	fn claim(&self, hash: H256, at: Option<BlockHash>) -> Result<Option<(AccountId, BlockNumber)>>;
+
+	/// Up to `limit` claims owned by `account`, skipping the first `start`.
+	///
+	/// `limit` is capped at `MAX_CLAIMS_PER_PAGE`.
+	#[rpc(name = "poe_claimsOf")]
+	fn claims_of(
+		&self,
+		account: AccountId,
+		start: u32,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<H256>>;
+
+	/// The chain of custody of the claim on the digest `hash`, oldest first.
+	#[rpc(name = "poe_claimHistory")]
+	fn claim_history(
+		&self,
+		hash: H256,
+		at: Option<BlockHash>,
+	) -> Result<Vec<CustodyRecord<AccountId, BlockNumber>>>;
+
+	/// The claim on the digest `hash` with a storage read proof of it and of its
+	/// expiry, to check against the state root of the block with `pallet-poe-verifier`.
+	#[rpc(name = "poe_claimProof")]
+	fn claim_proof(
+		&self,
+		hash: H256,
+		at: Option<BlockHash>,
+	) -> Result<ClaimProof<BlockHash, AccountId, BlockNumber>>;
+}
+
+/// Implements the PoE RPC methods on top of the `PoeApi` runtime API.
+pub struct Poe<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Poe<C, B> {
+	/// Create a new `Poe` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber> PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber>
+	for Poe<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block>,
+	C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec,
+	BlockNumber: Codec,
+{
+	fn claim(
+		&self,
+		hash: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<(AccountId, BlockNumber)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.claim(&at, hash.into())
+			.map_err(|e| runtime_error("Unable to query claim.", e))
+	}
+
+	fn claims_of(
+		&self,
+		account: AccountId,
+		start: u32,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<H256>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let claims = api.claims_of(&at, account, start, limit.min(MAX_CLAIMS_PER_PAGE))
+			.map_err(|e| runtime_error("Unable to query claims.", e))?;
+
+		Ok(claims.into_iter().map(H256::from).collect())
+	}
+
+	fn claim_history(
+		&self,
+		hash: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<CustodyRecord<AccountId, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.claim_history(&at, hash.into())
+			.map_err(|e| runtime_error("Unable to query claim history.", e))
+	}
+
+	fn claim_proof(
+		&self,
+		hash: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ClaimProof<<Block as BlockT>::Hash, AccountId, BlockNumber>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+		let at = BlockId::hash(at_hash);
+
+		let claim = api.claim(&at, hash.into())
+			.map_err(|e| runtime_error("Unable to query claim.", e))?;
+		let key = api.claim_key(&at, hash.into())
+			.map_err(|e| runtime_error("Unable to query claim key.", e))?;
+		let expiry_key = api.claim_expiry_key(&at, hash.into())
+			.map_err(|e| runtime_error("Unable to query claim expiry key.", e))?;
+		let proof = self.client.read_proof(&at, &mut [key.as_slice(), expiry_key.as_slice()].iter().copied())
+			.map_err(|e| runtime_error("Unable to read claim proof.", e))?;
+
+		Ok(ClaimProof {
+			at: at_hash,
+			claim,
+			key: key.into(),
+			expiry_key: expiry_key.into(),
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		})
+	}
+}
@@ -0,0 +1,33 @@
+//! Runtime API definition for the proof of existence pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+pub use pallet_poe::{ClaimHash, CollectionId, CustodyReason, CustodyRecord};
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi<AccountId, BlockNumber> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Hash `document` with the runtime's claim hasher and return the owner and
+		/// block of its claim, if there is one.
+		fn verify(document: Vec<u8>) -> Option<(AccountId, BlockNumber)>;
+		/// Return the owner and block of the claim on the digest `hash`, if there is one.
+		fn claim(hash: ClaimHash) -> Option<(AccountId, BlockNumber)>;
+		/// Return up to `limit` claims owned by `account`, skipping the first `start`.
+		fn claims_of(account: AccountId, start: u32, limit: u32) -> Vec<ClaimHash>;
+		/// Check that the document digest `leaf` belongs to the batch claimed under
+		/// `root`, and return the owner and block of the batch claim.
+		fn verify_inclusion(leaf: ClaimHash, proof: Vec<ClaimHash>, root: ClaimHash) -> Option<(AccountId, BlockNumber)>;
+		/// Return the chain of custody of the claim on `hash`, oldest first.
+		fn claim_history(hash: ClaimHash) -> Vec<CustodyRecord<AccountId, BlockNumber>>;
+		/// Return the storage key of the claim on `hash`.
+		fn claim_key(hash: ClaimHash) -> Vec<u8>;
+		/// Return the storage key of the expiry of the claim on `hash`.
+		fn claim_expiry_key(hash: ClaimHash) -> Vec<u8>;
+		/// Return the owner and block of the claim on `hash` in `collection`, if there is one.
+		fn collection_claim(collection: CollectionId, hash: ClaimHash) -> Option<(AccountId, BlockNumber)>;
+	}
+}
@@ -0,0 +1,296 @@
+//! Benchmarking setup for pallet-poe
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as PoeModule;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{EnsureOrigin, Get};
+use frame_system::RawOrigin;
+use sp_runtime::{
+	traits::{Bounded, IdentifyAccount},
+	KeyTypeId, MultiSignature, MultiSigner,
+};
+use sp_std::{vec, vec::Vec};
+
+/// An account with enough funds for any claim deposit.
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let who = account(name, index, 0);
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+	who
+}
+
+/// A claim of the largest allowed length, made by `owner`.
+fn setup_claim<T: Config>(owner: &T::AccountId) -> ClaimHash {
+	setup_expiring_claim::<T>(owner, None)
+}
+
+/// A claim of the largest allowed length, made by `owner` and expiring at `expires_at`.
+fn setup_expiring_claim<T: Config>(owner: &T::AccountId, expires_at: Option<T::BlockNumber>) -> ClaimHash {
+	let proof = vec![1u8; T::MaxClaimLen::get() as usize];
+	PoeModule::<T>::create_claim(RawOrigin::Signed(owner.clone()).into(), proof.clone(), expires_at)
+		.expect("a funded account can claim; qed");
+	T::Hasher::hash(&proof)
+}
+
+/// A collection created by `owner`, taking claims of up to `MaxCollectionClaimLen` bytes.
+fn setup_collection<T: Config>(owner: &T::AccountId) -> CollectionId {
+	let id = NextCollectionId::<T>::get();
+	let admin = T::Lookup::unlookup(owner.clone());
+	PoeModule::<T>::create_collection(
+		RawOrigin::Signed(owner.clone()).into(),
+		admin,
+		Some(T::MaxCollectionClaimLen::get()),
+	)
+	.expect("a funded account can create a collection; qed");
+	id
+}
+
+/// The key type of the keys the benchmarks sign with.
+const BENCH_KEY_TYPE: KeyTypeId = KeyTypeId(*b"bpoe");
+
+/// An `OffchainSignature` the benchmarks can produce, to sign the payload of
+/// `create_claim_signed` with a fresh key.
+pub trait BenchmarkSignature<Public>: Sized {
+	/// Sign `payload`, returning the signing key and the signature.
+	fn sign(payload: &[u8]) -> (Public, Self);
+}
+
+impl BenchmarkSignature<MultiSigner> for MultiSignature {
+	fn sign(payload: &[u8]) -> (MultiSigner, Self) {
+		// Sr25519, the scheme of the node template's accounts.
+		let public = sp_io::crypto::sr25519_generate(BENCH_KEY_TYPE, None);
+		let signature = sp_io::crypto::sr25519_sign(BENCH_KEY_TYPE, &public, payload)
+			.expect("the key was just generated; qed");
+		(public.into(), signature.into())
+	}
+}
+
+#[cfg(test)]
+impl BenchmarkSignature<sp_runtime::testing::UintAuthorityId> for sp_runtime::testing::TestSignature {
+	fn sign(payload: &[u8]) -> (sp_runtime::testing::UintAuthorityId, Self) {
+		(sp_runtime::testing::UintAuthorityId(1), sp_runtime::testing::TestSignature(1, payload.to_vec()))
+	}
+}
+
+benchmarks! {
+	where_clause { where T::OffchainSignature: BenchmarkSignature<T::OffchainPublic> }
+
+	create_claim {
+		// Hashing is the only part of the cost that depends on the claim length.
+		let l in 1 .. T::MaxClaimLen::get();
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+		let proof: Vec<u8> = vec![1u8; l as usize];
+		let hash = T::Hasher::hash(&proof);
+	}: _(RawOrigin::Signed(caller.clone()), proof, None)
+	verify {
+		assert_eq!(Proofs::<T>::get(&hash).owner, caller);
+	}
+
+	create_claim_signed {
+		// Verifying the signature hashes the whole payload, claim included.
+		let l in 1 .. T::MaxClaimLen::get();
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let proof: Vec<u8> = vec![1u8; l as usize];
+		let hash = T::Hasher::hash(&proof);
+		let payload = PoeModule::<T>::signed_claim_payload(&proof, 0);
+		let (signer, signature) = T::OffchainSignature::sign(&payload);
+		let owner = signer.into_account();
+	}: _(RawOrigin::Signed(caller), proof, owner.clone(), signature, 0)
+	verify {
+		assert_eq!(Proofs::<T>::get(&hash).owner, owner);
+	}
+
+	revoke_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let hash = setup_claim::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), hash)
+	verify {
+		assert!(!Proofs::<T>::contains_key(&hash));
+	}
+
+	transfer_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let dest: T::AccountId = funded_account::<T>("dest", 0);
+		let hash = setup_claim::<T>(&caller);
+		let dest_lookup = T::Lookup::unlookup(dest.clone());
+	}: _(RawOrigin::Signed(caller), hash, dest_lookup)
+	verify {
+		assert_eq!(Proofs::<T>::get(&hash).owner, dest);
+	}
+
+	offer_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let dest: T::AccountId = account("dest", 0, 0);
+		let hash = setup_claim::<T>(&caller);
+		let dest_lookup = T::Lookup::unlookup(dest.clone());
+	}: _(RawOrigin::Signed(caller), hash, dest_lookup)
+	verify {
+		assert_eq!(Offers::<T>::get(&hash), Some(dest));
+	}
+
+	accept_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let dest: T::AccountId = funded_account::<T>("dest", 0);
+		let hash = setup_claim::<T>(&caller);
+		PoeModule::<T>::offer_claim(RawOrigin::Signed(caller).into(), hash, T::Lookup::unlookup(dest.clone()))
+			.expect("the owner can offer a claim; qed");
+	}: _(RawOrigin::Signed(dest.clone()), hash)
+	verify {
+		assert_eq!(Proofs::<T>::get(&hash).owner, dest);
+	}
+
+	cancel_offer {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let dest: T::AccountId = account("dest", 0, 0);
+		let hash = setup_claim::<T>(&caller);
+		PoeModule::<T>::offer_claim(RawOrigin::Signed(caller.clone()).into(), hash, T::Lookup::unlookup(dest))
+			.expect("the owner can offer a claim; qed");
+	}: _(RawOrigin::Signed(caller), hash)
+	verify {
+		assert!(!Offers::<T>::contains_key(&hash));
+	}
+
+	create_batch_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let root = T::Hasher::hash(&b"root"[..]);
+	}: _(RawOrigin::Signed(caller.clone()), root, 1_000)
+	verify {
+		assert_eq!(Proofs::<T>::get(&root).owner, caller);
+	}
+
+	renew_claim {
+		// Replacing an expiry costs more than setting the first one.
+		frame_system::Module::<T>::set_block_number(1u32.into());
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let hash = setup_expiring_claim::<T>(&caller, Some(10u32.into()));
+	}: _(RawOrigin::Signed(caller), hash, Some(20u32.into()))
+	verify {
+		assert_eq!(ClaimExpiry::<T>::get(&hash), Some(20u32.into()));
+	}
+
+	dispute_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let challenger: T::AccountId = funded_account::<T>("challenger", 0);
+		let hash = setup_claim::<T>(&caller);
+		let evidence = T::Hasher::hash(&b"evidence"[..]);
+	}: _(RawOrigin::Signed(challenger), hash, evidence)
+	verify {
+		assert!(Disputes::<T>::contains_key(&hash));
+	}
+
+	resolve_dispute {
+		// The worst case: the claim is reassigned, then expires as it ran out during
+		// the dispute.
+		frame_system::Module::<T>::set_block_number(1u32.into());
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let challenger: T::AccountId = funded_account::<T>("challenger", 0);
+		let hash = setup_expiring_claim::<T>(&caller, Some(2u32.into()));
+		let evidence = T::Hasher::hash(&b"evidence"[..]);
+		PoeModule::<T>::dispute_claim(RawOrigin::Signed(challenger).into(), hash, evidence)
+			.expect("a funded account can dispute a claim; qed");
+		frame_system::Module::<T>::set_block_number(2u32.into());
+		let origin = T::ArbitratorOrigin::successful_origin();
+	}: {
+		PoeModule::<T>::resolve_dispute(origin, hash, true).map_err(|e| e.error)?;
+	}
+	verify {
+		assert!(!Proofs::<T>::contains_key(&hash));
+	}
+
+	create_claims {
+		// Short proofs, the hashing of longer ones is priced with `create_claim`.
+		let n in 1 .. T::MaxBatchSize::get();
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let proofs: Vec<Vec<u8>> = (0..n).map(|i| i.encode()).collect();
+		let last = T::Hasher::hash(&proofs[proofs.len() - 1]);
+	}: _(RawOrigin::Signed(caller.clone()), proofs, BatchMode::AllOrNothing)
+	verify {
+		assert_eq!(Proofs::<T>::get(&last).owner, caller);
+	}
+
+	revoke_claims {
+		let n in 1 .. T::MaxBatchSize::get();
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let proofs: Vec<Vec<u8>> = (0..n).map(|i| i.encode()).collect();
+		let hashes: Vec<ClaimHash> = proofs.iter().map(|proof| T::Hasher::hash(proof)).collect();
+		PoeModule::<T>::create_claims(RawOrigin::Signed(caller.clone()).into(), proofs, BatchMode::AllOrNothing)
+			.expect("a funded account can claim; qed");
+	}: _(RawOrigin::Signed(caller), hashes.clone(), BatchMode::AllOrNothing)
+	verify {
+		assert!(hashes.iter().all(|hash| !Proofs::<T>::contains_key(hash)));
+	}
+
+	approve_operator {
+		let caller: T::AccountId = whitelisted_caller();
+		let operator: T::AccountId = account("operator", 0, 0);
+		let operator_lookup = T::Lookup::unlookup(operator.clone());
+	}: _(RawOrigin::Signed(caller.clone()), operator_lookup)
+	verify {
+		assert!(Operators::<T>::contains_key(&caller, &operator));
+	}
+
+	revoke_operator {
+		let caller: T::AccountId = whitelisted_caller();
+		let operator: T::AccountId = account("operator", 0, 0);
+		let operator_lookup = T::Lookup::unlookup(operator.clone());
+		PoeModule::<T>::approve_operator(RawOrigin::Signed(caller.clone()).into(), operator_lookup.clone())
+			.expect("anyone can approve an operator; qed");
+	}: _(RawOrigin::Signed(caller.clone()), operator_lookup)
+	verify {
+		assert!(!Operators::<T>::contains_key(&caller, &operator));
+	}
+
+	set_claim_operator {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let operator: T::AccountId = account("operator", 0, 0);
+		let hash = setup_claim::<T>(&caller);
+		let operator_lookup = T::Lookup::unlookup(operator.clone());
+	}: _(RawOrigin::Signed(caller), hash, Some(operator_lookup))
+	verify {
+		assert_eq!(ClaimOperators::<T>::get(&hash), Some(operator));
+	}
+
+	create_collection {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let admin = T::Lookup::unlookup(caller.clone());
+		let id = NextCollectionId::<T>::get();
+	}: _(RawOrigin::Signed(caller), admin, Some(T::MaxCollectionClaimLen::get()))
+	verify {
+		assert!(Collections::<T>::contains_key(id));
+	}
+
+	destroy_collection {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let id = setup_collection::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), id)
+	verify {
+		assert!(!Collections::<T>::contains_key(id));
+	}
+
+	create_collection_claim {
+		let l in 1 .. T::MaxCollectionClaimLen::get();
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let id = setup_collection::<T>(&caller);
+		let proof: Vec<u8> = vec![1u8; l as usize];
+		let hash = T::Hasher::hash(&proof);
+	}: _(RawOrigin::Signed(caller), id, proof)
+	verify {
+		assert!(CollectionProofs::<T>::contains_key(id, &hash));
+	}
+
+	revoke_collection_claim {
+		let caller: T::AccountId = funded_account::<T>("caller", 0);
+		let id = setup_collection::<T>(&caller);
+		let proof = vec![1u8; T::MaxCollectionClaimLen::get() as usize];
+		let hash = T::Hasher::hash(&proof);
+		PoeModule::<T>::create_collection_claim(RawOrigin::Signed(caller.clone()).into(), id, proof)
+			.expect("a funded account can claim; qed");
+	}: _(RawOrigin::Signed(caller), id, hash)
+	verify {
+		assert!(!CollectionProofs::<T>::contains_key(id, &hash));
+	}
+}
+
+impl_benchmark_test_suite!(PoeModule, crate::mock::new_test_ext(), crate::mock::Test);
@@ -5,6 +5,11 @@
 /// <https://substrate.dev/docs/en/knowledgebase/runtime/frame>
 
 pub use pallet::*;
+use codec::{Decode, Encode};
+use frame_support::traits::{Currency, Time};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
 use sp_runtime::traits::StaticLookup;
 #[cfg(test)]
 mod mock;
@@ -12,15 +17,167 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
-// #[cfg(feature = "runtime-benchmarks")]
-// mod benchmarking;
+pub mod merkle;
+pub mod migrations;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+/// Fixed-size content digest a claim is keyed by.
+pub type ClaimHash = [u8; 32];
+
+/// Identifier of a collection of claims.
+pub type CollectionId = u32;
+
+/// Tag that keeps `create_claim_signed` signatures from being valid for any other message.
+pub const SIGNED_CLAIM_TAG: &[u8] = b"poe/create_claim_signed";
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+pub type MomentOf<T> = <<T as Config>::Time as Time>::Moment;
+
+pub type NegativeImbalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+
+/// Hashes document content into the digest a claim is stored under.
+pub trait ContentHasher {
+	fn hash(data: &[u8]) -> ClaimHash;
+}
+
+/// Blake2b-256, the hasher used elsewhere in Substrate.
+pub struct Blake2Hasher;
+
+impl ContentHasher for Blake2Hasher {
+	fn hash(data: &[u8]) -> ClaimHash {
+		sp_io::hashing::blake2_256(data)
+	}
+}
+
+/// SHA2-256, matching the digests produced by common tooling such as `sha256sum`.
+pub struct Sha2Hasher;
+
+impl ContentHasher for Sha2Hasher {
+	fn hash(data: &[u8]) -> ClaimHash {
+		sp_io::hashing::sha2_256(data)
+	}
+}
+
+/// A claim on a document: who holds it, and since when.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct Claim<AccountId, BlockNumber, Moment> {
+	/// The current owner.
+	pub owner: AccountId,
+	/// The block at which the owner got the claim.
+	pub block: BlockNumber,
+	/// The timestamp of that block.
+	pub moment: Moment,
+	/// Whether `moment` was estimated from the block number by a migration rather
+	/// than read from the timestamp pallet.
+	pub estimated: bool,
+}
+
+/// A registry of claims apart from the global one, managed by an admin.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct CollectionDetails<AccountId, Balance> {
+	/// The account that created the collection and paid its deposit.
+	pub owner: AccountId,
+	/// The deposit reserved from `owner` until the collection is destroyed.
+	pub deposit: Balance,
+	/// The account allowed to revoke any claim of the collection.
+	pub admin: AccountId,
+	/// The longest claim accepted, in place of `MaxClaimLen` if set. At most
+	/// `MaxCollectionClaimLen`.
+	pub max_claim_len: Option<u32>,
+	/// The number of claims in the collection.
+	pub claims: u32,
+}
+
+/// How `create_claims` and `revoke_claims` handle a failing item.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BatchMode {
+	/// The first failing item fails the whole batch, nothing is applied.
+	AllOrNothing,
+	/// Every item is tried, and its result reported with a `BatchItemResult` event.
+	BestEffort,
+}
+
+/// How an account came to hold a claim.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CustodyReason {
+	/// The account made the claim.
+	Created,
+	/// The previous owner transferred the claim directly.
+	Transferred,
+	/// The account accepted an offer from the previous owner.
+	OfferAccepted,
+	/// The claim was carried over by a storage migration, earlier owners are unknown.
+	Migrated,
+	/// The arbitrator awarded the claim to the account in a dispute.
+	Arbitration,
+	/// The claim was part of the genesis configuration.
+	Genesis,
+}
+
+/// A challenge of a claim, pending until the arbitrator resolves it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Dispute<AccountId, Balance> {
+	/// The account contesting the claim.
+	pub challenger: AccountId,
+	/// Digest of the evidence backing the challenge, kept off chain.
+	pub evidence: ClaimHash,
+	/// The bond reserved from the challenger.
+	pub bond: Balance,
+}
+
+/// One owner's custody of a claim, from the block they got it until the block they
+/// lost it, if they did.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct CustodyRecord<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	pub from_block: BlockNumber,
+	pub to_block: Option<BlockNumber>,
+	pub reason: CustodyReason,
+}
+
+/// Storage layouts of this pallet, used to decide which migrations still need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// `Proofs` keyed by the raw claim bytes. Also the state of a chain coming from
+	/// the `decl_storage` pallet, whose claims are still under its own prefix.
+	V1_0_0,
+	/// `Proofs` keyed by the content digest of the claim.
+	V2_0_0,
+	/// `Proofs` holding a [`Claim`] with the timestamp of the claim.
+	V3_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+	use frame_support::{
+		dispatch::{DispatchResult, DispatchResultWithPostInfo},
+		storage::{with_transaction, TransactionOutcome},
+		pallet_prelude::*,
+		traits::{BalanceStatus, GenesisBuild, OnUnbalanced, ReservableCurrency, Time},
+	};
 	use frame_system::pallet_prelude::*;
-    use sp_std::vec::Vec; 
-    use sp_runtime::traits::StaticLookup;
+    use sp_std::vec::Vec;
+    use sp_runtime::traits::{IdentifyAccount, One, Saturating, StaticLookup, Verify, Zero};
+    pub use crate::weights::WeightInfo;
+    use super::{
+        BalanceOf, BatchMode, Claim, ClaimHash, CollectionDetails, CollectionId, ContentHasher, CustodyReason,
+        CustodyRecord, Dispute, MomentOf, NegativeImbalanceOf, Releases,
+    };
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
@@ -30,26 +187,203 @@ pub mod pallet {
 
     #[pallet::constant]
 		type MaxClaimLen: Get<u32>;
+
+		/// The hasher used to turn document content into a claim digest.
+		type Hasher: ContentHasher;
+
+		/// The maximum number of expired claims removed in a single block.
+		#[pallet::constant]
+		type MaxExpiriesPerBlock: Get<u32>;
+
+		/// The currency in which claim deposits are reserved.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The base deposit reserved for every claim.
+		#[pallet::constant]
+		type DepositBase: Get<BalanceOf<Self>>;
+
+		/// The additional deposit reserved per byte of claim.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+
+		/// The maximum number of custody records kept per claim. Once reached, the oldest
+		/// record is dropped to make room for the next one.
+		#[pallet::constant]
+		type MaxHistoryLen: Get<u32>;
+
+		/// Whether `transfer_claim` may move a claim without the receiver accepting it.
+		/// When `false`, claims change hands only through `offer_claim` / `accept_claim`.
+		#[pallet::constant]
+		type AllowDirectTransfer: Get<bool>;
+
+		/// The source of the timestamp recorded with each claim, usually `pallet_timestamp`.
+		type Time: Time;
+
+		/// The time the chain started at, used to estimate the timestamp of claims
+		/// made before timestamps were recorded.
+		#[pallet::constant]
+		type GenesisMoment: Get<MomentOf<Self>>;
+
+		/// The expected time between two blocks, used with `GenesisMoment`.
+		#[pallet::constant]
+		type ExpectedBlockTime: Get<MomentOf<Self>>;
+
+		/// The bond reserved from an account disputing a claim.
+		#[pallet::constant]
+		type DisputeBond: Get<BalanceOf<Self>>;
+
+		/// The origin that resolves disputes, e.g. root or a collective.
+		type ArbitratorOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Handler for the bonds and deposits slashed when a dispute is resolved.
+		type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The maximum number of claims in a `create_claims` or `revoke_claims` batch.
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		/// The deposit reserved from the creator of a collection until it is destroyed.
+		#[pallet::constant]
+		type CollectionDeposit: Get<BalanceOf<Self>>;
+
+		/// The highest claim length limit a collection may set in place of `MaxClaimLen`.
+		#[pallet::constant]
+		type MaxCollectionClaimLen: Get<u32>;
+
+		/// The signature checked by `create_claim_signed`, e.g. `MultiSignature`.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// The key behind an `OffchainSignature`, identifying the account it signs for.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// Information on runtime weights.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
 
-    #[pallet::storage] 
-    pub(super) type Proofs<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, T::BlockNumber), ValueQuery>;  
+    #[pallet::storage]
+    pub(super) type Proofs<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, Claim<T::AccountId, T::BlockNumber, MomentOf<T>>, ValueQuery>;
+
+    /// The chain of custody of each claim, oldest first, at most `MaxHistoryLen` records.
+    /// Removed together with the claim, so it only takes space while a deposit is held.
+    #[pallet::storage]
+    pub(super) type History<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, Vec<CustodyRecord<T::AccountId, T::BlockNumber>>, ValueQuery>;
+
+    /// Index of the claims owned by each account.
+    #[pallet::storage]
+    pub(super) type ClaimsOf<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, ClaimHash, (), ValueQuery>;
+
+    /// The number of leaves of the claims that are Merkle roots of a batch.
+    #[pallet::storage]
+    pub(super) type BatchLeafCount<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, u32, OptionQuery>;
+
+    /// Pending offers of a claim to a new owner.
+    #[pallet::storage]
+    pub(super) type Offers<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, T::AccountId, OptionQuery>;
+
+    /// The account holding the deposit of a claim, and the amount reserved.
+    #[pallet::storage]
+    pub(super) type ClaimDeposits<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, (T::AccountId, BalanceOf<T>), OptionQuery>;
+
+    /// The block at which a claim expires, for claims created with an expiry.
+    #[pallet::storage]
+    pub(super) type ClaimExpiry<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, T::BlockNumber, OptionQuery>;
+
+    /// Index of claims by the block they expire at, swept in `on_initialize`.
+    #[pallet::storage]
+    pub(super) type ExpiryQueue<T: Config> = StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, ClaimHash, (), ValueQuery>;
+
+    /// The first block of `ExpiryQueue` that has not been fully swept yet.
+    #[pallet::storage]
+    pub(super) type ExpiryCursor<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+    /// Pending disputes. A disputed claim is locked until the dispute is resolved.
+    #[pallet::storage]
+    pub(super) type Disputes<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, Dispute<T::AccountId, BalanceOf<T>>, OptionQuery>;
+
+    /// The collections of claims, by id.
+    #[pallet::storage]
+    pub(super) type Collections<T: Config> = StorageMap<_, Twox64Concat, CollectionId, CollectionDetails<T::AccountId, BalanceOf<T>>, OptionQuery>;
+
+    /// The id of the next collection.
+    #[pallet::storage]
+    pub(super) type NextCollectionId<T: Config> = StorageValue<_, CollectionId, ValueQuery>;
+
+    /// The claims of each collection, with the deposit reserved from their owner.
+    ///
+    /// These are a lighter kind of claim than `Proofs`: they are not in the owner index
+    /// `ClaimsOf` and have no custody history or expiry. They cannot be transferred,
+    /// offered, disputed, batched or managed by operators; only their owner or the
+    /// collection admin can revoke them.
+    #[pallet::storage]
+    pub(super) type CollectionProofs<T: Config> = StorageDoubleMap<_, Twox64Concat, CollectionId, Blake2_128Concat, ClaimHash, (Claim<T::AccountId, T::BlockNumber, MomentOf<T>>, BalanceOf<T>), OptionQuery>;
+
+    /// The operators each owner allows to transfer and revoke all of their claims.
+    #[pallet::storage]
+    pub(super) type Operators<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, (), ValueQuery>;
+
+    /// An account the owner allows to transfer or revoke a single claim. Cleared
+    /// when the claim changes hands.
+    #[pallet::storage]
+    pub(super) type ClaimOperators<T: Config> = StorageMap<_, Blake2_128Concat, ClaimHash, T::AccountId, OptionQuery>;
+
+    /// The nonce expected in the next signed claim of each account.
+    #[pallet::storage]
+    pub(super) type Nonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+    /// The storage layout currently in use, see [`Releases`].
+    #[pallet::storage]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://substrate.dev/docs/en/knowledgebase/runtime/events
 	#[pallet::event]
-	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::metadata(T::AccountId = "AccountId", T::BlockNumber = "BlockNumber", MomentOf<T> = "Moment")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-        /// Event emitted when a proof has been claimed. [who, claim]
-        ClaimCreated(T::AccountId, Vec<u8>),
-        /// Event emitted when a claim is revoked by the owner. [who, claim]
-        ClaimRevoked(T::AccountId, Vec<u8>),
-        ClaimTrans(T::AccountId, T::AccountId, Vec<u8>),
+        /// Event emitted when a proof has been claimed. [who, claim, moment]
+        ClaimCreated(T::AccountId, ClaimHash, MomentOf<T>),
+        /// Event emitted when a claim is revoked by or for the owner. [owner, claim]
+        ClaimRevoked(T::AccountId, ClaimHash),
+        ClaimTrans(T::AccountId, T::AccountId, ClaimHash),
+        /// A claim was offered to a new owner. [owner, to, claim]
+        ClaimOffered(T::AccountId, T::AccountId, ClaimHash),
+        /// An offer was accepted and the claim changed hands. [from, to, claim]
+        OfferAccepted(T::AccountId, T::AccountId, ClaimHash),
+        /// A pending offer was withdrawn by the owner. [owner, claim]
+        OfferCancelled(T::AccountId, ClaimHash),
+        /// A Merkle root covering a batch of documents has been claimed. [who, root, leaf_count]
+        BatchClaimCreated(T::AccountId, ClaimHash, u32),
+        /// The expiry of a claim was changed by its owner. [who, claim, expires_at]
+        ClaimRenewed(T::AccountId, ClaimHash, Option<T::BlockNumber>),
+        /// A claim reached its expiry block and was removed. [owner, claim]
+        ClaimExpired(T::AccountId, ClaimHash),
+        /// A claim was disputed and is locked until resolved. [challenger, claim, evidence]
+        ClaimDisputed(T::AccountId, ClaimHash, ClaimHash),
+        /// The arbitrator resolved a dispute in favour of an account. [winner, claim]
+        DisputeResolved(T::AccountId, ClaimHash),
+        /// An owner allowed an operator to manage all of their claims. [owner, operator]
+        OperatorApproved(T::AccountId, T::AccountId),
+        /// An owner withdrew the approval of an operator. [owner, operator]
+        OperatorRevoked(T::AccountId, T::AccountId),
+        /// An owner set or cleared the operator of a single claim. [owner, claim, operator]
+        ClaimOperatorSet(T::AccountId, ClaimHash, Option<T::AccountId>),
+        /// An operator transferred or revoked a claim for its owner, see the preceding
+        /// event. [operator, owner, claim]
+        OperatorActed(T::AccountId, T::AccountId, ClaimHash),
+        /// The result of an item of a best-effort batch. [index, claim, result]
+        BatchItemResult(u32, ClaimHash, DispatchResult),
+        /// A collection was created. [collection, admin]
+        CollectionCreated(CollectionId, T::AccountId),
+        /// A collection was destroyed and its deposit returned. [collection]
+        CollectionDestroyed(CollectionId),
+        /// A proof was claimed in a collection. [who, collection, claim]
+        CollectionClaimCreated(T::AccountId, CollectionId, ClaimHash),
+        /// A claim of a collection was revoked by its owner or the admin. [who, collection, claim]
+        CollectionClaimRevoked(T::AccountId, CollectionId, ClaimHash),
 	}
 
 	// Errors inform users that something went wrong.
@@ -59,24 +393,128 @@ pub mod pallet {
         ProofAlreadyExist,
         /// The proof does not exist, so it cannot be revoked.
         ClaimNotExist,
-        /// The proof is claimed by another account, so caller can't revoke it.
+        /// The proof is claimed by another account, who did not make the caller an
+        /// operator, so caller can't revoke it.
         NotClaimOwner,
         /// The proof length too long
-        InvalidClaimLength
+        InvalidClaimLength,
+        /// The expiry block is not in the future.
+        InvalidExpiry,
+        /// Direct transfers are disabled, use `offer_claim` instead.
+        DirectTransferDisabled,
+        /// There is no pending offer for this claim.
+        NoPendingOffer,
+        /// The pending offer is made to another account.
+        NotOfferRecipient,
+        /// A batch must contain at least one document.
+        EmptyBatch,
+        /// The claim is under dispute and locked.
+        ClaimLocked,
+        /// The claim is already under dispute.
+        AlreadyDisputed,
+        /// There is no pending dispute for this claim.
+        NoDispute,
+        /// The owner of a claim cannot dispute it.
+        CannotDisputeOwnClaim,
+        /// The nonce of a signed claim is not the one expected for the owner.
+        InvalidNonce,
+        /// The signature of a signed claim does not match the owner.
+        BadSignature,
+        /// The batch has more than `MaxBatchSize` items.
+        BatchTooLarge,
+        /// The account is not an approved operator of the owner.
+        NotOperator,
+        /// There is no collection with this id.
+        UnknownCollection,
+        /// All collection ids are taken.
+        NoAvailableCollectionId,
+        /// The claim length limit is above `MaxCollectionClaimLen`.
+        CollectionClaimLenTooHigh,
+        /// Only the creator of the collection can do this.
+        NotCollectionOwner,
+        /// The collection still holds claims.
+        CollectionNotEmpty,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Claims to seed the chain with, as (proof, owner, block). No deposit is
+		/// reserved for them and their timestamp is estimated from the block.
+		pub claims: Vec<(Vec<u8>, T::AccountId, T::BlockNumber)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { claims: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (proof, owner, block) in &self.claims {
+				assert!(
+					proof.len() <= T::MaxClaimLen::get() as usize,
+					"genesis claim longer than MaxClaimLen",
+				);
+				let hash = T::Hasher::hash(proof);
+				assert!(!Proofs::<T>::contains_key(&hash), "duplicate claim in genesis");
+
+				Proofs::<T>::insert(&hash, Claim {
+					owner: owner.clone(),
+					block: *block,
+					moment: crate::migrations::estimate_moment::<T>(*block),
+					estimated: true,
+				});
+				ClaimsOf::<T>::insert(owner, &hash, ());
+				History::<T>::append(&hash, CustodyRecord {
+					owner: owner.clone(),
+					from_block: *block,
+					to_block: None,
+					reason: CustodyReason::Genesis,
+				});
+			}
+
+			// A new chain starts out on the latest layout, there is nothing to migrate.
+			StorageVersion::<T>::put(Releases::V3_0_0);
+		}
 	}
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			Self::sweep_expired(n)
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::<T>::get() == Releases::V3_0_0 {
+				return 0;
+			}
+
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() == Releases::V1_0_0 {
+				weight += crate::migrations::migrate_from_legacy::<T>();
+				weight += crate::migrations::migrate_to_v2::<T>();
+			}
+			if StorageVersion::<T>::get() == Releases::V2_0_0 {
+				weight += crate::migrations::migrate_to_v3::<T>();
+			}
+			weight
+		}
+	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
 	// These functions materialize as "extrinsics", which are often compared to transactions.
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
-            #[pallet::weight(1_000)]
+            /// Claim a document by submitting its content, which is hashed on chain.
+            #[pallet::weight(T::WeightInfo::create_claim(proof.len() as u32))]
             pub(super) fn create_claim(
                 origin: OriginFor<T>,
                 proof: Vec<u8>,
+                expires_at: Option<T::BlockNumber>,
             ) -> DispatchResultWithPostInfo {
 
                 // Check that the extrinsic was signed and get the signer.
@@ -88,70 +526,918 @@ pub mod pallet {
                   proof.len() <= T::MaxClaimLen::get() as usize,
                   Error::<T>::InvalidClaimLength
                 );
-            
-                // Verify that the specified proof has not already been claimed.         
-                ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyExist);
 
-                // Get the block number from the FRAME System module.
-                let current_block = <frame_system::Module<T>>::block_number();
+                Self::do_create_claim(&sender, sender.clone(), T::Hasher::hash(&proof), proof.len(), expires_at)?;
 
-                // Store the proof with the sender and block number.
-                Proofs::<T>::insert(&proof, (&sender, current_block));
+                Ok(().into())
+            }
 
-                // Emit an event that the claim was created.
-                Self::deposit_event(Event::ClaimCreated(sender, proof));
+            /// Claim `proof` in the name of `owner`, who signed the payload of
+            /// `signed_claim_payload` off chain. The sender relays the claim and pays
+            /// its deposit.
+            ///
+            /// `nonce` must be the current nonce of `owner`, see `Nonces`.
+            #[pallet::weight(T::WeightInfo::create_claim_signed(proof.len() as u32))]
+            pub(super) fn create_claim_signed(
+                origin: OriginFor<T>,
+                proof: Vec<u8>,
+                owner: T::AccountId,
+                signature: T::OffchainSignature,
+                nonce: u64,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(
+                  proof.len() <= T::MaxClaimLen::get() as usize,
+                  Error::<T>::InvalidClaimLength
+                );
+
+                ensure!(nonce == Nonces::<T>::get(&owner), Error::<T>::InvalidNonce);
+
+                let payload = Self::signed_claim_payload(&proof, nonce);
+                ensure!(signature.verify(&payload[..], &owner), Error::<T>::BadSignature);
+
+                Self::do_create_claim(&sender, owner.clone(), T::Hasher::hash(&proof), proof.len(), None)?;
+                Nonces::<T>::insert(&owner, nonce + 1);
 
                 Ok(().into())
             }
 
-            #[pallet::weight(10_000)]
+            /// Claim a document by its digest, computed off chain with `T::Hasher`.
+            #[pallet::weight(T::WeightInfo::create_claim(0))]
+            pub(super) fn create_claim_for_hash(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                expires_at: Option<T::BlockNumber>,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                Self::do_create_claim(&sender, sender.clone(), hash, hash.len(), expires_at)?;
+
+                Ok(().into())
+            }
+
+            #[pallet::weight(T::WeightInfo::revoke_claim())]
             pub(super) fn revoke_claim(
                 origin: OriginFor<T>,
-                proof: Vec<u8>,
+                hash: ClaimHash,
             ) -> DispatchResultWithPostInfo {
                 // Check that the extrinsic was signed and get the signer.
                 // This function will return an error if the extrinsic is not signed.
                 // https://substrate.dev/docs/en/knowledgebase/runtime/origin
                 let sender = ensure_signed(origin)?;
 
-                // Verify that the specified proof has been claimed.
-                ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::ClaimNotExist);
+                // Verify that the specified proof has been claimed and has not expired.
+                ensure!(Self::is_live(&hash), Error::<T>::ClaimNotExist);
 
-                // Get owner of the claim.
-                let (owner, _) = Proofs::<T>::get(&proof);
+                // Verify that sender of the current call is the claim owner or acts for them.
+                let owner = Self::ensure_can_manage(&hash, &sender)?;
 
-                // Verify that sender of the current call is the claim owner.
-                ensure!(sender == owner, Error::<T>::NotClaimOwner);
+                Self::ensure_not_disputed(&hash)?;
 
                 // Remove claim from storage.
-                Proofs::<T>::remove(&proof);
+                Self::remove_claim(&hash);
 
                 // Emit an event that the claim was erased.
-                Self::deposit_event(Event::ClaimRevoked(sender, proof));
+                Self::deposit_event(Event::ClaimRevoked(owner.clone(), hash));
+                Self::note_operator(sender, owner, hash);
 
                 Ok(().into())
             }
 
-            #[pallet::weight(10_000)]
+            #[pallet::weight(T::WeightInfo::transfer_claim())]
             pub(super) fn transfer_claim(
-                origin: OriginFor<T>, 
-                proof: Vec<u8>, dest: <T::Lookup as sp_runtime::traits::StaticLookup>::Source
+                origin: OriginFor<T>,
+                hash: ClaimHash, dest: <T::Lookup as sp_runtime::traits::StaticLookup>::Source
             ) -> DispatchResultWithPostInfo {
                 let sender = ensure_signed(origin)?;
 
-                ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::ClaimNotExist);
+                ensure!(T::AllowDirectTransfer::get(), Error::<T>::DirectTransferDisabled);
 
-                let (owner, _block_number) = Proofs::<T>::get(&proof);
+                let owner = Self::ensure_can_manage(&hash, &sender)?;
 
-                ensure!(owner == sender, Error::<T>::NotClaimOwner);
+                Self::ensure_not_disputed(&hash)?;
 
                 let dest = T::Lookup::lookup(dest)?;
 
-                Proofs::<T>::insert(&proof, (&dest, <frame_system::Module<T>>::block_number()));
+                Self::do_transfer(&hash, &owner, &dest, CustodyReason::Transferred)?;
+                Self::note_operator(sender, owner, hash);
 
-                Self::deposit_event(Event::ClaimTrans(sender, dest, proof));
+                Ok(().into())
+            }
+
+            /// Offer a claim to `to`, who becomes the owner once they call `accept_claim`.
+            ///
+            /// A new offer replaces any pending one.
+            #[pallet::weight(T::WeightInfo::offer_claim())]
+            pub(super) fn offer_claim(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                to: <T::Lookup as StaticLookup>::Source,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                Self::ensure_claim_owner(&hash, &sender)?;
+                Self::ensure_not_disputed(&hash)?;
+
+                let to = T::Lookup::lookup(to)?;
+
+                Offers::<T>::insert(&hash, &to);
+
+                Self::deposit_event(Event::ClaimOffered(sender, to, hash));
 
                 Ok(().into())
             }
+
+            /// Accept a pending offer, taking over the claim and its deposit.
+            #[pallet::weight(T::WeightInfo::accept_claim())]
+            pub(super) fn accept_claim(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let to = Offers::<T>::get(&hash).ok_or(Error::<T>::NoPendingOffer)?;
+
+                ensure!(to == sender, Error::<T>::NotOfferRecipient);
+
+                ensure!(Self::is_live(&hash), Error::<T>::ClaimNotExist);
+
+                Self::ensure_not_disputed(&hash)?;
+
+                let owner = Proofs::<T>::get(&hash).owner;
+
+                Self::do_transfer(&hash, &owner, &sender, CustodyReason::OfferAccepted)?;
+
+                Self::deposit_event(Event::OfferAccepted(owner, sender, hash));
+
+                Ok(().into())
+            }
+
+            /// Withdraw the pending offer of a claim.
+            #[pallet::weight(T::WeightInfo::cancel_offer())]
+            pub(super) fn cancel_offer(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                Self::ensure_claim_owner(&hash, &sender)?;
+
+                ensure!(Offers::<T>::contains_key(&hash), Error::<T>::NoPendingOffer);
+
+                Offers::<T>::remove(&hash);
+
+                Self::deposit_event(Event::OfferCancelled(sender, hash));
+
+                Ok(().into())
+            }
+
+            /// Claim all documents of a batch at once through the root of a Merkle tree
+            /// over their digests, see the [`merkle`](crate::merkle) module.
+            ///
+            /// The root is an ordinary claim that can be transferred or revoked, each
+            /// document is checked against it with `verify_inclusion`.
+            #[pallet::weight(T::WeightInfo::create_batch_claim())]
+            pub(super) fn create_batch_claim(
+                origin: OriginFor<T>,
+                root: ClaimHash,
+                leaf_count: u32,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+
+                Self::do_create_claim(&sender, sender.clone(), root, root.len(), None)?;
+                BatchLeafCount::<T>::insert(&root, leaf_count);
+
+                Self::deposit_event(Event::BatchClaimCreated(sender, root, leaf_count));
+
+                Ok(().into())
+            }
+
+            /// Move the expiry of a claim to `expires_at`, or make it permanent with `None`.
+            #[pallet::weight(T::WeightInfo::renew_claim())]
+            pub(super) fn renew_claim(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                expires_at: Option<T::BlockNumber>,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(Self::is_live(&hash), Error::<T>::ClaimNotExist);
+
+                let owner = Proofs::<T>::get(&hash).owner;
+
+                ensure!(owner == sender, Error::<T>::NotClaimOwner);
+
+                // Otherwise the owner could end a dispute by letting the claim expire.
+                Self::ensure_not_disputed(&hash)?;
+
+                Self::ensure_valid_expiry(expires_at)?;
+
+                if let Some(old) = ClaimExpiry::<T>::take(&hash) {
+                    ExpiryQueue::<T>::remove(old, &hash);
+                }
+                Self::schedule_expiry(&hash, expires_at);
+
+                Self::deposit_event(Event::ClaimRenewed(sender, hash, expires_at));
+
+                Ok(().into())
+            }
+
+            /// Contest the claim on `hash`, backed by evidence with the digest `evidence`.
+            ///
+            /// Reserves `T::DisputeBond` from the sender and locks the claim until
+            /// `T::ArbitratorOrigin` calls `resolve_dispute`.
+            #[pallet::weight(T::WeightInfo::dispute_claim())]
+            pub(super) fn dispute_claim(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                evidence: ClaimHash,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(Self::is_live(&hash), Error::<T>::ClaimNotExist);
+
+                ensure!(Proofs::<T>::get(&hash).owner != sender, Error::<T>::CannotDisputeOwnClaim);
+
+                ensure!(!Disputes::<T>::contains_key(&hash), Error::<T>::AlreadyDisputed);
+
+                let bond = T::DisputeBond::get();
+                T::Currency::reserve(&sender, bond)?;
+                Disputes::<T>::insert(&hash, Dispute { challenger: sender.clone(), evidence, bond });
+
+                Self::deposit_event(Event::ClaimDisputed(sender, hash, evidence));
+
+                Ok(().into())
+            }
+
+            /// Resolve the dispute of the claim on `hash`.
+            ///
+            /// If `reassign` is set, the claim goes to the challenger, whose bond becomes
+            /// the claim deposit, and the deposit of the previous owner is slashed. A deposit
+            /// paid by a sponsor through `create_claim_signed` is returned to the sponsor
+            /// instead, who is not liable for the owner's claim.
+            /// Otherwise the owner keeps the claim and the bond of the challenger is slashed.
+            ///
+            /// A claim that reached its expiry during the dispute expires once it is resolved.
+            #[pallet::weight(T::WeightInfo::resolve_dispute())]
+            pub(super) fn resolve_dispute(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                reassign: bool,
+            ) -> DispatchResultWithPostInfo {
+                T::ArbitratorOrigin::ensure_origin(origin)?;
+
+                let dispute = Disputes::<T>::get(&hash).ok_or(Error::<T>::NoDispute)?;
+                let owner = Proofs::<T>::get(&hash).owner;
+
+                let winner = if reassign {
+                    if let Some((depositor, deposit)) = ClaimDeposits::<T>::take(&hash) {
+                        if depositor == owner {
+                            let (imbalance, _) = T::Currency::slash_reserved(&depositor, deposit);
+                            T::Slashed::on_unbalanced(imbalance);
+                        } else {
+                            T::Currency::unreserve(&depositor, deposit);
+                        }
+                    }
+                    ClaimDeposits::<T>::insert(&hash, (&dispute.challenger, dispute.bond));
+                    Self::do_transfer(&hash, &owner, &dispute.challenger, CustodyReason::Arbitration)?;
+                    dispute.challenger
+                } else {
+                    let (imbalance, _) = T::Currency::slash_reserved(&dispute.challenger, dispute.bond);
+                    T::Slashed::on_unbalanced(imbalance);
+                    owner
+                };
+                Disputes::<T>::remove(&hash);
+
+                Self::deposit_event(Event::DisputeResolved(winner, hash));
+
+                if Self::is_expired(&hash) {
+                    Self::expire_claim(&hash);
+                }
+
+                Ok(().into())
+            }
+
+            /// Claim several documents at once, handling failures according to `mode`.
+            #[pallet::weight(Pallet::<T>::create_claims_weight(proofs))]
+            pub(super) fn create_claims(
+                origin: OriginFor<T>,
+                proofs: Vec<Vec<u8>>,
+                mode: BatchMode,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(proofs.len() <= T::MaxBatchSize::get() as usize, Error::<T>::BatchTooLarge);
+
+                let hashes: Vec<ClaimHash> = proofs.iter().map(|proof| T::Hasher::hash(proof)).collect();
+                Self::run_batch(mode, &hashes, |index, hash| {
+                    let len = proofs[index].len();
+                    ensure!(len <= T::MaxClaimLen::get() as usize, Error::<T>::InvalidClaimLength);
+                    Self::do_create_claim(&sender, sender.clone(), *hash, len, None)
+                })?;
+
+                Ok(().into())
+            }
+
+            /// Revoke several claims at once, handling failures according to `mode`.
+            ///
+            /// Each claim is checked as in `revoke_claim`.
+            #[pallet::weight(T::WeightInfo::revoke_claims(hashes.len() as u32))]
+            pub(super) fn revoke_claims(
+                origin: OriginFor<T>,
+                hashes: Vec<ClaimHash>,
+                mode: BatchMode,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                ensure!(hashes.len() <= T::MaxBatchSize::get() as usize, Error::<T>::BatchTooLarge);
+
+                Self::run_batch(mode, &hashes, |_, hash| {
+                    let owner = Self::ensure_can_manage(hash, &sender)?;
+                    Self::ensure_not_disputed(hash)?;
+                    Self::remove_claim(hash);
+                    Self::deposit_event(Event::ClaimRevoked(owner.clone(), *hash));
+                    Self::note_operator(sender.clone(), owner, *hash);
+                    Ok(())
+                })?;
+
+                Ok(().into())
+            }
+
+            /// Allow `operator` to transfer and revoke all claims of the sender.
+            #[pallet::weight(T::WeightInfo::approve_operator())]
+            pub(super) fn approve_operator(
+                origin: OriginFor<T>,
+                operator: <T::Lookup as StaticLookup>::Source,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let operator = T::Lookup::lookup(operator)?;
+
+                Operators::<T>::insert(&sender, &operator, ());
+
+                Self::deposit_event(Event::OperatorApproved(sender, operator));
+
+                Ok(().into())
+            }
+
+            /// Withdraw the approval of `operator` given with `approve_operator`.
+            #[pallet::weight(T::WeightInfo::revoke_operator())]
+            pub(super) fn revoke_operator(
+                origin: OriginFor<T>,
+                operator: <T::Lookup as StaticLookup>::Source,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let operator = T::Lookup::lookup(operator)?;
+
+                ensure!(Operators::<T>::contains_key(&sender, &operator), Error::<T>::NotOperator);
+
+                Operators::<T>::remove(&sender, &operator);
+
+                Self::deposit_event(Event::OperatorRevoked(sender, operator));
+
+                Ok(().into())
+            }
+
+            /// Allow `operator` to transfer or revoke the claim on `hash`, or clear the
+            /// approval with `None`. Only the owner can approve.
+            #[pallet::weight(T::WeightInfo::set_claim_operator())]
+            pub(super) fn set_claim_operator(
+                origin: OriginFor<T>,
+                hash: ClaimHash,
+                operator: Option<<T::Lookup as StaticLookup>::Source>,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                Self::ensure_claim_owner(&hash, &sender)?;
+
+                let operator = operator.map(T::Lookup::lookup).transpose()?;
+
+                match &operator {
+                    Some(operator) => ClaimOperators::<T>::insert(&hash, operator),
+                    None => ClaimOperators::<T>::remove(&hash),
+                }
+
+                Self::deposit_event(Event::ClaimOperatorSet(sender, hash, operator));
+
+                Ok(().into())
+            }
+
+            /// Create a collection managed by `admin`, accepting claims of up to
+            /// `max_claim_len` bytes, or `MaxClaimLen` if not set.
+            ///
+            /// `CollectionDeposit` is reserved from the sender until they destroy the
+            /// collection with `destroy_collection`.
+            #[pallet::weight(T::WeightInfo::create_collection())]
+            pub(super) fn create_collection(
+                origin: OriginFor<T>,
+                admin: <T::Lookup as StaticLookup>::Source,
+                max_claim_len: Option<u32>,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let admin = T::Lookup::lookup(admin)?;
+
+                if let Some(max_claim_len) = max_claim_len {
+                    ensure!(
+                        max_claim_len <= T::MaxCollectionClaimLen::get(),
+                        Error::<T>::CollectionClaimLenTooHigh
+                    );
+                }
+
+                let id = NextCollectionId::<T>::get();
+                let next = id.checked_add(1).ok_or(Error::<T>::NoAvailableCollectionId)?;
+
+                let deposit = T::CollectionDeposit::get();
+                T::Currency::reserve(&sender, deposit)?;
+
+                NextCollectionId::<T>::put(next);
+                Collections::<T>::insert(id, CollectionDetails {
+                    owner: sender,
+                    deposit,
+                    admin: admin.clone(),
+                    max_claim_len,
+                    claims: 0,
+                });
+
+                Self::deposit_event(Event::CollectionCreated(id, admin));
+
+                Ok(().into())
+            }
+
+            /// Destroy an empty collection and return its deposit to its creator.
+            #[pallet::weight(T::WeightInfo::destroy_collection())]
+            pub(super) fn destroy_collection(
+                origin: OriginFor<T>,
+                collection: CollectionId,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let details = Collections::<T>::get(collection).ok_or(Error::<T>::UnknownCollection)?;
+                ensure!(sender == details.owner, Error::<T>::NotCollectionOwner);
+                ensure!(details.claims == 0, Error::<T>::CollectionNotEmpty);
+
+                Collections::<T>::remove(collection);
+                T::Currency::unreserve(&details.owner, details.deposit);
+
+                Self::deposit_event(Event::CollectionDestroyed(collection));
+
+                Ok(().into())
+            }
+
+            /// Claim a document in `collection`, reserving the usual deposit.
+            ///
+            /// The claim is independent of any claim on the same document in the global
+            /// registry or in other collections. See `CollectionProofs` for what collection
+            /// claims do not support.
+            #[pallet::weight(T::WeightInfo::create_collection_claim(proof.len() as u32))]
+            pub(super) fn create_collection_claim(
+                origin: OriginFor<T>,
+                collection: CollectionId,
+                proof: Vec<u8>,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let mut details = Collections::<T>::get(collection).ok_or(Error::<T>::UnknownCollection)?;
+
+                let max_len = details.max_claim_len.unwrap_or_else(T::MaxClaimLen::get);
+                ensure!(proof.len() <= max_len as usize, Error::<T>::InvalidClaimLength);
+
+                let hash = T::Hasher::hash(&proof);
+                ensure!(
+                    !CollectionProofs::<T>::contains_key(collection, &hash),
+                    Error::<T>::ProofAlreadyExist
+                );
+
+                let deposit = Self::deposit_for(proof.len());
+                T::Currency::reserve(&sender, deposit)?;
+                CollectionProofs::<T>::insert(collection, &hash, (Self::new_claim(sender.clone()), deposit));
+                details.claims = details.claims.saturating_add(1);
+                Collections::<T>::insert(collection, details);
+
+                Self::deposit_event(Event::CollectionClaimCreated(sender, collection, hash));
+
+                Ok(().into())
+            }
+
+            /// Revoke a claim of `collection`, as its owner or as the collection admin.
+            #[pallet::weight(T::WeightInfo::revoke_collection_claim())]
+            pub(super) fn revoke_collection_claim(
+                origin: OriginFor<T>,
+                collection: CollectionId,
+                hash: ClaimHash,
+            ) -> DispatchResultWithPostInfo {
+                let sender = ensure_signed(origin)?;
+
+                let mut details = Collections::<T>::get(collection).ok_or(Error::<T>::UnknownCollection)?;
+
+                let (claim, deposit) = CollectionProofs::<T>::get(collection, &hash)
+                    .ok_or(Error::<T>::ClaimNotExist)?;
+
+                ensure!(sender == claim.owner || sender == details.admin, Error::<T>::NotClaimOwner);
+
+                CollectionProofs::<T>::remove(collection, &hash);
+                T::Currency::unreserve(&claim.owner, deposit);
+                details.claims = details.claims.saturating_sub(1);
+                Collections::<T>::insert(collection, details);
+
+                Self::deposit_event(Event::CollectionClaimRevoked(sender, collection, hash));
+
+                Ok(().into())
+            }
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Record `owner` as the claimant of `hash` at the current block, reserving
+		/// the deposit for a claim of `len` bytes from `depositor`.
+		fn do_create_claim(
+			depositor: &T::AccountId,
+			owner: T::AccountId,
+			hash: ClaimHash,
+			len: usize,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			// Verify that the specified proof has not already been claimed. A claim past
+			// its expiry that the sweep has not reached yet is removed here instead,
+			// unless it is disputed.
+			let exists = Proofs::<T>::contains_key(&hash);
+			ensure!(
+				!exists || (Self::is_expired(&hash) && !Disputes::<T>::contains_key(&hash)),
+				Error::<T>::ProofAlreadyExist
+			);
+
+			Self::ensure_valid_expiry(expires_at)?;
+
+			let deposit = Self::deposit_for(len);
+			T::Currency::reserve(depositor, deposit)?;
+
+			if exists {
+				Self::expire_claim(&hash);
+			}
+			ClaimDeposits::<T>::insert(&hash, (depositor, deposit));
+
+			Self::schedule_expiry(&hash, expires_at);
+
+			// Store the proof with the sender, block number and timestamp.
+			let claim = Self::new_claim(owner.clone());
+			let moment = claim.moment;
+			Proofs::<T>::insert(&hash, claim);
+			ClaimsOf::<T>::insert(&owner, &hash, ());
+			Self::open_custody(&hash, &owner, CustodyReason::Created);
+
+			// Emit an event that the claim was created.
+			Self::deposit_event(Event::ClaimCreated(owner, hash, moment));
+
+			Ok(())
+		}
+
+		/// A claim held by `owner` from the current block on.
+		fn new_claim(owner: T::AccountId) -> Claim<T::AccountId, T::BlockNumber, MomentOf<T>> {
+			Claim {
+				owner,
+				block: <frame_system::Module<T>>::block_number(),
+				moment: T::Time::now(),
+				estimated: false,
+			}
+		}
+
+		/// Ensure `who` owns the claim on `hash` or is an operator of it, and return
+		/// the owner.
+		fn ensure_can_manage(hash: &ClaimHash, who: &T::AccountId) -> Result<T::AccountId, DispatchError> {
+			ensure!(Self::is_live(hash), Error::<T>::ClaimNotExist);
+			let owner = Proofs::<T>::get(hash).owner;
+			let allowed = &owner == who
+				|| Operators::<T>::contains_key(&owner, who)
+				|| ClaimOperators::<T>::get(hash).as_ref() == Some(who);
+			ensure!(allowed, Error::<T>::NotClaimOwner);
+			Ok(owner)
+		}
+
+		/// Record that `who` acted on the claim on `hash` for `owner`, if they are
+		/// not the owner.
+		fn note_operator(who: T::AccountId, owner: T::AccountId, hash: ClaimHash) {
+			if who != owner {
+				Self::deposit_event(Event::OperatorActed(who, owner, hash));
+			}
+		}
+
+		/// Ensure `hash` is claimed by `who`.
+		fn ensure_claim_owner(hash: &ClaimHash, who: &T::AccountId) -> DispatchResult {
+			ensure!(Self::is_live(hash), Error::<T>::ClaimNotExist);
+			let owner = Proofs::<T>::get(hash).owner;
+			ensure!(&owner == who, Error::<T>::NotClaimOwner);
+			Ok(())
+		}
+
+		/// Move `hash` and its deposit from `owner` to `dest`, dropping any pending offer.
+		fn do_transfer(
+			hash: &ClaimHash,
+			owner: &T::AccountId,
+			dest: &T::AccountId,
+			reason: CustodyReason,
+		) -> DispatchResult {
+			Self::move_deposit(hash, owner, dest)?;
+
+			Self::close_custody(hash);
+			Self::open_custody(hash, dest, reason);
+
+			Proofs::<T>::insert(hash, Self::new_claim(dest.clone()));
+			ClaimsOf::<T>::remove(owner, hash);
+			ClaimsOf::<T>::insert(dest, hash, ());
+			Offers::<T>::remove(hash);
+			ClaimOperators::<T>::remove(hash);
+
+			Self::deposit_event(Event::ClaimTrans(owner.clone(), dest.clone(), *hash));
+
+			Ok(())
+		}
+
+		/// Apply `f` to each item of a batch, by index and claim.
+		///
+		/// `f` must check an item before changing anything, so that a failing item
+		/// of a best-effort batch leaves no trace.
+		fn run_batch(
+			mode: BatchMode,
+			hashes: &[ClaimHash],
+			mut f: impl FnMut(usize, &ClaimHash) -> DispatchResult,
+		) -> DispatchResult {
+			match mode {
+				BatchMode::AllOrNothing => with_transaction(|| {
+					for (index, hash) in hashes.iter().enumerate() {
+						if let Err(e) = f(index, hash) {
+							return TransactionOutcome::Rollback(Err(e));
+						}
+					}
+					TransactionOutcome::Commit(Ok(()))
+				}),
+				BatchMode::BestEffort => {
+					for (index, hash) in hashes.iter().enumerate() {
+						let result = f(index, hash);
+						Self::deposit_event(Event::BatchItemResult(index as u32, *hash, result));
+					}
+					Ok(())
+				}
+			}
+		}
+
+		/// The weight of `create_claims` for `proofs`: the per-item cost, plus the
+		/// hashing cost of their total length.
+		fn create_claims_weight(proofs: &[Vec<u8>]) -> Weight {
+			let bytes = proofs.iter().fold(0u32, |total, proof| total.saturating_add(proof.len() as u32));
+			let hashing = T::WeightInfo::create_claim(bytes).saturating_sub(T::WeightInfo::create_claim(0));
+			T::WeightInfo::create_claims(proofs.len() as u32).saturating_add(hashing)
+		}
+
+		/// Ensure `hash` is not locked by a pending dispute.
+		fn ensure_not_disputed(hash: &ClaimHash) -> DispatchResult {
+			ensure!(!Disputes::<T>::contains_key(hash), Error::<T>::ClaimLocked);
+			Ok(())
+		}
+
+		/// Start the custody of `owner` over `hash` at the current block, dropping the
+		/// oldest records if the history is full.
+		fn open_custody(hash: &ClaimHash, owner: &T::AccountId, reason: CustodyReason) {
+			let from_block = <frame_system::Module<T>>::block_number();
+			let max = T::MaxHistoryLen::get().max(1) as usize;
+			History::<T>::mutate(hash, |history| {
+				if history.len() >= max {
+					history.drain(..history.len() + 1 - max);
+				}
+				history.push(CustodyRecord { owner: owner.clone(), from_block, to_block: None, reason });
+			});
+		}
+
+		/// End the current custody over `hash` at the current block.
+		fn close_custody(hash: &ClaimHash) {
+			History::<T>::mutate(hash, |history| {
+				if let Some(current) = history.last_mut() {
+					current.to_block = Some(<frame_system::Module<T>>::block_number());
+				}
+			});
+		}
+
+		/// Ensure `expires_at`, if set, is a future block.
+		fn ensure_valid_expiry(expires_at: Option<T::BlockNumber>) -> DispatchResult {
+			if let Some(expires_at) = expires_at {
+				ensure!(
+					expires_at > <frame_system::Module<T>>::block_number(),
+					Error::<T>::InvalidExpiry
+				);
+			}
+			Ok(())
+		}
+
+		/// Index `hash` to expire at `expires_at`.
+		fn schedule_expiry(hash: &ClaimHash, expires_at: Option<T::BlockNumber>) {
+			if let Some(expires_at) = expires_at {
+				ClaimExpiry::<T>::insert(hash, expires_at);
+				ExpiryQueue::<T>::insert(expires_at, hash, ());
+			}
+		}
+
+		/// The deposit for a claim of `len` bytes.
+		pub fn deposit_for(len: usize) -> BalanceOf<T> {
+			let per_byte = T::DepositPerByte::get().saturating_mul((len as u32).into());
+			T::DepositBase::get().saturating_add(per_byte)
+		}
+
+		/// Hand the deposit of `hash` over from `owner` to `dest`, who keeps it reserved.
+		///
+		/// A deposit paid by a sponsor rather than by `owner` stays with the sponsor
+		/// until the claim is removed.
+		fn move_deposit(hash: &ClaimHash, owner: &T::AccountId, dest: &T::AccountId) -> DispatchResult {
+			if let Some((depositor, deposit)) = ClaimDeposits::<T>::get(hash) {
+				if &depositor != owner {
+					return Ok(());
+				}
+				if &depositor != dest {
+					T::Currency::repatriate_reserved(&depositor, dest, deposit, BalanceStatus::Reserved)?;
+				}
+				ClaimDeposits::<T>::insert(hash, (dest, deposit));
+			}
+			Ok(())
+		}
+
+		/// Whether the claim on `hash` has reached its expiry block, swept or not.
+		fn is_expired(hash: &ClaimHash) -> bool {
+			ClaimExpiry::<T>::get(hash)
+				.map_or(false, |expires_at| expires_at <= <frame_system::Module<T>>::block_number())
+		}
+
+		/// Whether there is a claim on `hash` that has not expired.
+		fn is_live(hash: &ClaimHash) -> bool {
+			Proofs::<T>::contains_key(hash) && !Self::is_expired(hash)
+		}
+
+		/// Remove the expired claim on `hash`.
+		fn expire_claim(hash: &ClaimHash) {
+			let owner = Proofs::<T>::get(hash).owner;
+			Self::remove_claim(hash);
+			Self::deposit_event(Event::ClaimExpired(owner, *hash));
+		}
+
+		/// Remove a claim together with its expiry index entry, and return its deposit.
+		///
+		/// A pending dispute is dropped and its bond returned, there is nothing left
+		/// to contest.
+		fn remove_claim(hash: &ClaimHash) {
+			let owner = Proofs::<T>::take(hash).owner;
+			ClaimsOf::<T>::remove(&owner, hash);
+			Offers::<T>::remove(hash);
+			ClaimOperators::<T>::remove(hash);
+			BatchLeafCount::<T>::remove(hash);
+			History::<T>::remove(hash);
+			if let Some(expires_at) = ClaimExpiry::<T>::take(hash) {
+				ExpiryQueue::<T>::remove(expires_at, hash);
+			}
+			if let Some((depositor, deposit)) = ClaimDeposits::<T>::take(hash) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
+			if let Some(dispute) = Disputes::<T>::take(hash) {
+				T::Currency::unreserve(&dispute.challenger, dispute.bond);
+			}
+		}
+
+		/// Remove the claims that expire at or before block `n`, at most
+		/// `T::MaxExpiriesPerBlock` of them. Whatever does not fit is picked up in
+		/// the following blocks, starting from `ExpiryCursor`.
+		fn sweep_expired(n: T::BlockNumber) -> Weight {
+			let mut budget = T::MaxExpiriesPerBlock::get() as usize;
+			let mut cursor = ExpiryCursor::<T>::get();
+			if cursor.is_zero() {
+				cursor = n;
+			}
+			let mut reads: Weight = 1;
+			let mut writes: Weight = 0;
+
+			while cursor <= n && budget > 0 {
+				// Fetch one more than the budget to learn whether the block is done.
+				let due: Vec<ClaimHash> = ExpiryQueue::<T>::iter_prefix(cursor)
+					.map(|(hash, _)| hash)
+					.take(budget + 1)
+					.collect();
+				reads = reads.saturating_add(due.len() as Weight + 1);
+
+				let done = due.len() <= budget;
+				if due.is_empty() {
+					// Stepping over an empty block costs a read too; this also bounds how
+					// far a lagging cursor catches up in one go.
+					budget -= 1;
+				}
+				for hash in due.into_iter().take(budget) {
+					if Disputes::<T>::contains_key(&hash) {
+						// Left to `resolve_dispute`, so that letting a claim expire does
+						// not end its dispute.
+						ExpiryQueue::<T>::remove(cursor, &hash);
+						reads = reads.saturating_add(1);
+						writes = writes.saturating_add(1);
+					} else {
+						Self::expire_claim(&hash);
+						reads = reads.saturating_add(6);
+						writes = writes.saturating_add(11);
+					}
+					budget -= 1;
+				}
+
+				if !done {
+					break;
+				}
+				cursor = cursor.saturating_add(One::one());
+			}
+
+			ExpiryCursor::<T>::put(cursor);
+			writes = writes.saturating_add(1);
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		/// The owner and block of the claim on `hash`, if there is one.
+		///
+		/// A claim past its expiry block is reported as absent even if the sweep has
+		/// not removed it yet.
+		pub fn claim_of(hash: &ClaimHash) -> Option<(T::AccountId, T::BlockNumber)> {
+			if Self::is_live(hash) {
+				let claim = Proofs::<T>::get(hash);
+				Some((claim.owner, claim.block))
+			} else {
+				None
+			}
+		}
+
+		/// The storage key of the claim on `hash`, to request a read proof of it.
+		pub fn claim_key(hash: &ClaimHash) -> Vec<u8> {
+			Proofs::<T>::hashed_key_for(hash)
+		}
+
+		/// The storage key of the expiry of the claim on `hash`, proven along with the
+		/// claim since expired claims may not be swept yet.
+		pub fn claim_expiry_key(hash: &ClaimHash) -> Vec<u8> {
+			ClaimExpiry::<T>::hashed_key_for(hash)
+		}
+
+		/// The owner and block of the claim on `hash` in `collection`, if there is one.
+		pub fn collection_claim_of(
+			collection: CollectionId,
+			hash: &ClaimHash,
+		) -> Option<(T::AccountId, T::BlockNumber)> {
+			CollectionProofs::<T>::get(collection, hash).map(|(claim, _)| (claim.owner, claim.block))
+		}
+
+		/// Up to `limit` claims owned by `who`, skipping the first `start`.
+		///
+		/// The order is that of the storage index: stable between calls, but not the
+		/// order in which the claims were made.
+		pub fn claims_of(who: &T::AccountId, start: u32, limit: u32) -> Vec<ClaimHash> {
+			ClaimsOf::<T>::iter_prefix(who)
+				.map(|(hash, _)| hash)
+				.skip(start as usize)
+				.take(limit as usize)
+				.collect()
+		}
+
+		/// Check that the document digest `leaf` belongs to the batch claimed under
+		/// `root`, and return the owner and block of that batch claim.
+		///
+		/// `proof` lists the sibling digests from the leaf up, as produced by
+		/// [`merkle::MerkleTree::proof`](crate::merkle::MerkleTree).
+		pub fn verify_inclusion(
+			leaf: &ClaimHash,
+			proof: &[ClaimHash],
+			root: &ClaimHash,
+		) -> Option<(T::AccountId, T::BlockNumber)> {
+			let leaf_count = BatchLeafCount::<T>::get(root)?;
+			if proof.len() > crate::merkle::max_proof_len(leaf_count)
+				|| !crate::merkle::verify_inclusion::<T::Hasher>(leaf, proof, root)
+			{
+				return None;
+			}
+			Self::claim_of(root)
+		}
+
+		/// The message an owner signs to have `proof` claimed for them with `nonce`.
+		///
+		/// It starts with [`SIGNED_CLAIM_TAG`] and includes the genesis hash, so a
+		/// signature is only valid for this call on this chain.
+		pub fn signed_claim_payload(proof: &[u8], nonce: u64) -> Vec<u8> {
+			let genesis_hash = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+			(SIGNED_CLAIM_TAG, proof, nonce, genesis_hash).encode()
+		}
+
+		/// The chain of custody of `hash`, oldest first.
+		pub fn claim_history(hash: &ClaimHash) -> Vec<CustodyRecord<T::AccountId, T::BlockNumber>> {
+			History::<T>::get(hash)
+		}
+
+		/// Hash `document` with `T::Hasher` and look up its claim.
+		///
+		/// Meant for off-chain callers through the runtime API, the document never
+		/// has to be submitted on chain.
+		pub fn verify(document: &[u8]) -> Option<(T::AccountId, T::BlockNumber)> {
+			Self::claim_of(&T::Hasher::hash(document))
+		}
 	}
 }
@@ -0,0 +1,147 @@
+//! Merkle trees over claim digests, for notarizing many documents with one root.
+//!
+//! A leaf node is the hash of a `0x00` tag followed by the `ContentHasher` digest of
+//! a document. An inner node is the hash of a `0x01` tag followed by its two children
+//! in ascending order, so a proof is just the list of sibling digests from the leaf
+//! up, without left/right flags. A node without a sibling is carried up to the next
+//! level unchanged.
+//!
+//! With distinct tags no document digest can pass for an inner node: a document whose
+//! content is `0x01‖a‖b` has the digest of an inner node, but its leaf node is
+//! hashed once more under the leaf tag and so never lines up with the tree.
+
+use super::{ClaimHash, ContentHasher};
+use sp_std::vec::Vec;
+
+/// Prefix of leaf nodes.
+const LEAF_TAG: u8 = 0;
+/// Prefix of inner nodes, keeping them apart from leaf nodes.
+const NODE_TAG: u8 = 1;
+
+/// Hash the digest of a document into its leaf node.
+pub fn hash_leaf<H: ContentHasher>(digest: &ClaimHash) -> ClaimHash {
+	let mut data = Vec::with_capacity(1 + digest.len());
+	data.push(LEAF_TAG);
+	data.extend_from_slice(digest);
+	H::hash(&data)
+}
+
+/// Hash two sibling nodes into their parent.
+pub fn hash_pair<H: ContentHasher>(a: &ClaimHash, b: &ClaimHash) -> ClaimHash {
+	let (first, second) = if a <= b { (a, b) } else { (b, a) };
+	let mut data = Vec::with_capacity(1 + 2 * first.len());
+	data.push(NODE_TAG);
+	data.extend_from_slice(first);
+	data.extend_from_slice(second);
+	H::hash(&data)
+}
+
+/// Check that the document digest `leaf` is included in the tree with the given
+/// `root`, following the sibling digests in `proof` from the leaf up.
+pub fn verify_inclusion<H: ContentHasher>(leaf: &ClaimHash, proof: &[ClaimHash], root: &ClaimHash) -> bool {
+	let computed = proof.iter().fold(hash_leaf::<H>(leaf), |node, sibling| hash_pair::<H>(&node, sibling));
+	&computed == root
+}
+
+/// The most siblings a proof can have in a tree of `leaf_count` leaves.
+pub fn max_proof_len(leaf_count: u32) -> usize {
+	let mut depth = 0;
+	let mut width = leaf_count;
+	while width > 1 {
+		width = (width + 1) / 2;
+		depth += 1;
+	}
+	depth
+}
+
+#[cfg(feature = "std")]
+pub use builder::MerkleTree;
+
+#[cfg(feature = "std")]
+mod builder {
+	use super::*;
+	use std::{io, marker::PhantomData, path::Path};
+
+	/// A Merkle tree built off chain, to submit its root with `create_batch_claim`
+	/// and hand out inclusion proofs for the individual documents.
+	pub struct MerkleTree<H> {
+		/// The document digests, in leaf order.
+		digests: Vec<ClaimHash>,
+		/// All levels of the tree, from the leaf nodes up to the root.
+		levels: Vec<Vec<ClaimHash>>,
+		_hasher: PhantomData<H>,
+	}
+
+	impl<H: ContentHasher> MerkleTree<H> {
+		/// Build a tree over already computed document digests.
+		///
+		/// Returns `None` when there are no leaves.
+		pub fn from_leaves(digests: Vec<ClaimHash>) -> Option<Self> {
+			if digests.is_empty() {
+				return None;
+			}
+			let mut levels = vec![digests.iter().map(hash_leaf::<H>).collect::<Vec<_>>()];
+			while levels.last().map_or(false, |level| level.len() > 1) {
+				let next = levels
+					.last()
+					.expect("checked above; qed")
+					.chunks(2)
+					.map(|pair| match pair {
+						[a, b] => hash_pair::<H>(a, b),
+						[a] => *a,
+						_ => unreachable!("chunks of two; qed"),
+					})
+					.collect();
+				levels.push(next);
+			}
+			Some(Self { digests, levels, _hasher: PhantomData })
+		}
+
+		/// Build a tree over the contents of `documents`.
+		pub fn from_documents<D: AsRef<[u8]>>(documents: &[D]) -> Option<Self> {
+			Self::from_leaves(documents.iter().map(|d| H::hash(d.as_ref())).collect())
+		}
+
+		/// Build a tree over the files at `paths`, in the given order.
+		///
+		/// Returns `Ok(None)` when `paths` is empty.
+		pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> io::Result<Option<Self>> {
+			let leaves = paths
+				.iter()
+				.map(|path| std::fs::read(path).map(|content| H::hash(&content)))
+				.collect::<io::Result<Vec<_>>>()?;
+			Ok(Self::from_leaves(leaves))
+		}
+
+		/// The root to submit with `create_batch_claim`.
+		pub fn root(&self) -> ClaimHash {
+			self.levels.last().expect("a tree has at least one level; qed")[0]
+		}
+
+		/// The number of leaves, to submit with `create_batch_claim`.
+		pub fn leaf_count(&self) -> u32 {
+			self.levels[0].len() as u32
+		}
+
+		/// The document digest at `index`.
+		pub fn leaf(&self, index: usize) -> Option<ClaimHash> {
+			self.digests.get(index).copied()
+		}
+
+		/// The inclusion proof of the leaf at `index`.
+		pub fn proof(&self, index: usize) -> Option<Vec<ClaimHash>> {
+			if index >= self.levels[0].len() {
+				return None;
+			}
+			let mut proof = Vec::new();
+			let mut index = index;
+			for level in &self.levels[..self.levels.len() - 1] {
+				if let Some(sibling) = level.get(index ^ 1) {
+					proof.push(*sibling);
+				}
+				index /= 2;
+			}
+			Some(proof)
+		}
+	}
+}
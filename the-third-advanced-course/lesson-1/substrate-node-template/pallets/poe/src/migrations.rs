@@ -0,0 +1,191 @@
+//! Storage migrations for the PoE pallet.
+
+use super::*;
+use frame_support::{
+	storage::migration::{get_storage_value, put_storage_value, storage_key_iter, take_storage_value},
+	traits::{Get, PalletInfo},
+	weights::Weight,
+	Blake2_128Concat, StorageHasher,
+};
+use sp_runtime::traits::{SaturatedConversion, Saturating};
+use sp_std::vec::Vec;
+
+/// The storage prefix of the `decl_storage` PoE pallet this one replaces.
+pub const LEGACY_PREFIX: &[u8] = b"TemplateModule";
+
+/// Move the claims of the `decl_storage` pallet, stored under [`LEGACY_PREFIX`], over
+/// to this pallet in the `V1_0_0` layout, for `migrate_to_v2` to pick them up.
+///
+/// Claims are moved whatever their length, so none is lost to a `MaxClaimLen`
+/// smaller than the old `MaxClaimLength`. Deposits stay reserved from the owner
+/// and are indexed by the digest of the claim.
+///
+/// A legacy claim on bytes this pallet already holds a claim on is dropped rather
+/// than overwriting it, and its deposit is returned.
+///
+/// No runtime in this repository needs it: the node template runtime has run this
+/// pallet since genesis, and lesson-7 still runs the `decl_storage` pallet without
+/// this one. It is library code for a chain that swaps the latter for the former,
+/// which `on_runtime_upgrade` runs on a `V1_0_0` chain.
+pub fn migrate_from_legacy<T: Config>() -> Weight {
+	let pallet_name = match <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>() {
+		Some(name) => name,
+		None => return 0,
+	};
+
+	let old_claims: Vec<(Vec<u8>, (T::AccountId, T::BlockNumber))> = storage_key_iter::<
+		Vec<u8>,
+		(T::AccountId, T::BlockNumber),
+		Blake2_128Concat,
+	>(LEGACY_PREFIX, b"Proofs")
+		.drain()
+		.collect();
+
+	let count = old_claims.len() as Weight;
+	for (proof, (owner, block)) in old_claims {
+		let key = Blake2_128Concat::hash(&proof.encode());
+		let deposit = take_storage_value::<BalanceOf<T>>(LEGACY_PREFIX, b"ClaimDeposits", &key);
+		if get_storage_value::<(T::AccountId, T::BlockNumber)>(pallet_name.as_bytes(), b"Proofs", &key).is_some() {
+			frame_support::debug::warn!(
+				target: "runtime::poe",
+				"skipping legacy claim {:?} of {:?}, the same bytes are already claimed",
+				proof,
+				owner,
+			);
+			if let Some(deposit) = deposit {
+				T::Currency::unreserve(&owner, deposit);
+			}
+			continue;
+		}
+		if let Some(deposit) = deposit {
+			ClaimDeposits::<T>::insert(T::Hasher::hash(&proof), (&owner, deposit));
+		}
+		put_storage_value(pallet_name.as_bytes(), b"Proofs", &key, (owner, block));
+	}
+
+	T::DbWeight::get().reads_writes(count * 3, count * 3)
+}
+
+/// The number of claims left under [`LEGACY_PREFIX`].
+#[cfg(test)]
+fn legacy_claim_count<T: Config>() -> u32 {
+	storage_key_iter::<Vec<u8>, (T::AccountId, T::BlockNumber), Blake2_128Concat>(LEGACY_PREFIX, b"Proofs")
+		.count() as u32
+}
+
+/// The number of claims an upgrade should leave: those of this pallet, plus on a
+/// `V1_0_0` chain the legacy claims on bytes this pallet holds no claim on.
+///
+/// Iterates over every claim, so it is only meant for tests, to compare with
+/// [`post_upgrade_checks`].
+#[cfg(test)]
+pub fn pre_upgrade_count<T: Config>() -> u32 {
+	let pallet_name = match <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>() {
+		Some(name) => name,
+		None => return 0,
+	};
+	// Only called before the upgrade, when claims are still `(owner, block)` pairs.
+	let mut count = frame_support::storage::migration::StorageIterator::<(T::AccountId, T::BlockNumber)>::new(
+		pallet_name.as_bytes(),
+		b"Proofs",
+	)
+	.count() as u32;
+	if StorageVersion::<T>::get() == Releases::V1_0_0 {
+		count += storage_key_iter::<Vec<u8>, (T::AccountId, T::BlockNumber), Blake2_128Concat>(LEGACY_PREFIX, b"Proofs")
+			.filter(|(proof, _)| {
+				let key = Blake2_128Concat::hash(&proof.encode());
+				get_storage_value::<(T::AccountId, T::BlockNumber)>(pallet_name.as_bytes(), b"Proofs", &key).is_none()
+			})
+			.count() as u32;
+	}
+	count
+}
+
+/// Check that an upgrade left the storage in the latest layout, holding the
+/// `expected` claims, and return the number of claims checked.
+#[cfg(test)]
+pub fn post_upgrade_checks<T: Config>(expected: u32) -> Result<u32, &'static str> {
+	if StorageVersion::<T>::get() != Releases::V3_0_0 {
+		return Err("storage version not updated");
+	}
+	if legacy_claim_count::<T>() != 0 {
+		return Err("claims left under the legacy prefix");
+	}
+	let mut count = 0;
+	for (hash, claim) in Proofs::<T>::iter() {
+		if !ClaimsOf::<T>::contains_key(&claim.owner, &hash) {
+			return Err("claim missing from the owner index");
+		}
+		count += 1;
+	}
+	if count != expected {
+		return Err("claims lost or added in the upgrade");
+	}
+	Ok(count)
+}
+
+/// Re-key the claims of a `V1_0_0` chain, stored under their raw bytes, by their
+/// content digest.
+///
+/// Owners and block numbers are kept as they are, so raw-byte claims stay
+/// verifiable by submitting the same bytes to `verify`.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let pallet_name = match <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>() {
+		Some(name) => name,
+		None => return 0,
+	};
+
+	// Drain everything first: the new keys share the `Proofs` prefix and must not be
+	// picked up by the iterator.
+	let old_claims: Vec<(Vec<u8>, (T::AccountId, T::BlockNumber))> = storage_key_iter::<
+		Vec<u8>,
+		(T::AccountId, T::BlockNumber),
+		Blake2_128Concat,
+	>(pallet_name.as_bytes(), b"Proofs")
+		.drain()
+		.collect();
+
+	let count = old_claims.len() as Weight;
+	for (proof, claim) in old_claims {
+		let hash = T::Hasher::hash(&proof);
+		let (owner, block) = claim;
+		ClaimsOf::<T>::insert(&owner, &hash, ());
+		History::<T>::append(
+			&hash,
+			CustodyRecord { owner: owner.clone(), from_block: block, to_block: None, reason: CustodyReason::Migrated },
+		);
+		// Written in the `V2_0_0` layout, `migrate_to_v3` takes it from there.
+		put_storage_value(
+			pallet_name.as_bytes(),
+			b"Proofs",
+			&Blake2_128Concat::hash(&hash.encode()),
+			(owner, block),
+		);
+	}
+
+	StorageVersion::<T>::put(Releases::V2_0_0);
+
+	T::DbWeight::get().reads_writes(count + 1, count * 4 + 1)
+}
+
+/// Turn the `(owner, block)` claims of a `V2_0_0` chain into [`Claim`]s.
+///
+/// Their timestamp was never recorded, so it is estimated as `T::GenesisMoment`
+/// plus `T::ExpectedBlockTime` per block, and the claim is flagged as estimated.
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	let mut count: Weight = 0;
+	Proofs::<T>::translate::<(T::AccountId, T::BlockNumber), _>(|_hash, (owner, block)| {
+		count += 1;
+		Some(Claim { owner, block, moment: estimate_moment::<T>(block), estimated: true })
+	});
+
+	StorageVersion::<T>::put(Releases::V3_0_0);
+
+	T::DbWeight::get().reads_writes(count, count + 1)
+}
+
+/// The expected timestamp of `block`.
+pub(crate) fn estimate_moment<T: Config>(block: T::BlockNumber) -> MomentOf<T> {
+	let blocks: MomentOf<T> = block.saturated_into::<u64>().saturated_into();
+	T::GenesisMoment::get().saturating_add(blocks.saturating_mul(T::ExpectedBlockTime::get()))
+}
@@ -1,5 +1,6 @@
 use crate as pallet_poe;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::{Get, GenesisBuild}};
+use std::cell::RefCell;
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -18,7 +19,9 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
+		PoeModule: pallet_poe::{Module, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
@@ -45,22 +48,92 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	pub static ALLOW_DIRECT_TRANSFER: RefCell<bool> = RefCell::new(true);
+}
+
+pub struct AllowDirectTransfer;
+impl Get<bool> for AllowDirectTransfer {
+	fn get() -> bool {
+		ALLOW_DIRECT_TRANSFER.with(|v| *v.borrow())
+	}
+}
+
 parameter_types! {
 	pub const MaxClaimLen: u32 = 10;
+	pub const MaxExpiriesPerBlock: u32 = 2;
+	pub const DepositBase: u64 = 10;
+	pub const DepositPerByte: u64 = 1;
+	pub const MaxHistoryLen: u32 = 3;
+	pub const GenesisMoment: u64 = 1_000;
+	pub const ExpectedBlockTime: u64 = 6;
+	pub const DisputeBond: u64 = 20;
+	pub const MaxBatchSize: u32 = 3;
+	pub const CollectionDeposit: u64 = 10;
+	pub const MaxCollectionClaimLen: u32 = 20;
 }
 impl pallet_poe::Config for Test {
 	type Event = Event;
   type MaxClaimLen = MaxClaimLen;
+  type Hasher = pallet_poe::Blake2Hasher;
+  type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+  type Currency = Balances;
+  type DepositBase = DepositBase;
+  type DepositPerByte = DepositPerByte;
+  type MaxHistoryLen = MaxHistoryLen;
+  type AllowDirectTransfer = AllowDirectTransfer;
+  type Time = Timestamp;
+  type GenesisMoment = GenesisMoment;
+  type ExpectedBlockTime = ExpectedBlockTime;
+  type DisputeBond = DisputeBond;
+  type ArbitratorOrigin = frame_system::EnsureRoot<u64>;
+  type Slashed = ();
+  type MaxBatchSize = MaxBatchSize;
+  type CollectionDeposit = CollectionDeposit;
+  type MaxCollectionClaimLen = MaxCollectionClaimLen;
+  type OffchainSignature = sp_runtime::testing::TestSignature;
+  type OffchainPublic = sp_runtime::testing::UintAuthorityId;
+  type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100), (4, 5)],
+	}.assimilate_storage(&mut t).unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&pallet_poe::GenesisConfig::default(), &mut t).unwrap();
+	t.into()
 }
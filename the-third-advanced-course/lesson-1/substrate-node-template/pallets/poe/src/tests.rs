@@ -1,7 +1,24 @@
 use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::{OnInitialize, OnRuntimeUpgrade}, Blake2_128Concat, StorageHasher};
+use codec::Encode;
 use super::*;
 
+fn digest(claim: &[u8]) -> ClaimHash {
+  Blake2Hasher::hash(claim)
+}
+
+fn stored(hash: &ClaimHash) -> (u64, u64) {
+  let claim = Proofs::<Test>::get(hash);
+  (claim.owner, claim.block)
+}
+
+fn run_to_block(n: u64) {
+  while System::block_number() < n {
+    System::set_block_number(System::block_number() + 1);
+    PoeModule::on_initialize(System::block_number());
+  }
+}
+
 #[test]
 fn test_create_claim() {
 	new_test_ext().execute_with(|| {
@@ -11,55 +28,86 @@ fn test_create_claim() {
 		// assert_eq!(TemplateModule::something(), Some(42));
 
     assert_noop!(
-      PoeModule::create_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+      PoeModule::create_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], None),
       Error::<Test>::InvalidClaimLength
     );
 
 
     let claim = vec![0, 1];
-    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None));
 
-    assert_eq!( 
-      Proofs::<Test>::get(&claim), 
+    assert_eq!(
+      stored(&digest(&claim)),
       (1, frame_system::Pallet::<Test>::block_number())
     );
 
     assert_noop!(
-      PoeModule::create_claim(Origin::signed(1), claim.clone()),
+      PoeModule::create_claim(Origin::signed(1), claim.clone(), None),
+      Error::<Test>::ProofAlreadyExist
+    );
+	});
+}
+
+#[test]
+fn test_create_claim_for_hash() {
+	new_test_ext().execute_with(|| {
+    // A digest may come from a document far larger than `MaxClaimLen`.
+    let document = vec![7u8; 1024];
+    let hash = digest(&document);
+
+    assert_ok!(PoeModule::create_claim_for_hash(Origin::signed(1), hash, None));
+    assert_eq!(stored(&hash), (1, frame_system::Pallet::<Test>::block_number()));
+
+    assert_noop!(
+      PoeModule::create_claim_for_hash(Origin::signed(2), hash, None),
       Error::<Test>::ProofAlreadyExist
     );
 	});
 }
 
+#[test]
+fn test_verify() {
+	new_test_ext().execute_with(|| {
+    let document = vec![7u8; 1024];
+    assert_eq!(PoeModule::verify(&document), None);
+
+    assert_ok!(PoeModule::create_claim_for_hash(Origin::signed(1), digest(&document), None));
+
+    assert_eq!(PoeModule::verify(&document), Some((1, frame_system::Pallet::<Test>::block_number())));
+    assert_eq!(PoeModule::verify(&[0, 1]), None);
+	});
+}
+
 #[test]
 fn test_revoke_claim() {
 	new_test_ext().execute_with(|| {
 
     let claim = vec![0, 1];
+    let hash = digest(&claim);
 
     assert_noop!(
-      PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
+      PoeModule::revoke_claim(Origin::signed(1), hash),
       Error::<Test>::ClaimNotExist
     );
 
-    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None));
 
-    assert_eq!( 
-      Proofs::<Test>::get(&claim), 
+    assert_eq!(
+      stored(&hash),
       (1, frame_system::Pallet::<Test>::block_number())
     );
 
     assert_noop!(
-      PoeModule::revoke_claim(Origin::signed(2), claim.clone()),
+      PoeModule::revoke_claim(Origin::signed(2), hash),
       Error::<Test>::NotClaimOwner
     );
 
-    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
-    assert_eq!( 
-      Proofs::<Test>::get(&claim), 
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), hash));
+    assert_eq!(
+      stored(&hash),
       (0, 0)
     );
-	
+
 	});
 }
 
@@ -68,22 +116,1046 @@ fn test_revoke_claim() {
 fn test_transfer_claim() {
 	new_test_ext().execute_with(|| {
     let claim = vec![0, 1];
+    let hash = digest(&claim);
     assert_noop!(
-      PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2),
+      PoeModule::transfer_claim(Origin::signed(1), hash, 2),
       Error::<Test>::ClaimNotExist
     );
 
-    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None));
 
-    assert_eq!( 
-      Proofs::<Test>::get(&claim), 
+    assert_eq!(
+      stored(&hash),
       (1, frame_system::Pallet::<Test>::block_number())
     );
 
     assert_noop!(
-      PoeModule::transfer_claim(Origin::signed(2), claim.clone(), 3),
+      PoeModule::transfer_claim(Origin::signed(2), hash, 3),
+      Error::<Test>::NotClaimOwner
+    );
+
+	});
+}
+
+#[test]
+fn test_claim_deposit() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+
+    // 10 base plus 1 per byte of the two byte claim.
+    assert_eq!(PoeModule::deposit_for(2), 12);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    assert_eq!(Balances::reserved_balance(1), 12);
+    assert_eq!(Balances::free_balance(1), 88);
+
+    // A digest is charged for its own 32 bytes.
+    assert_ok!(PoeModule::create_claim_for_hash(Origin::signed(2), digest(&[2]), None));
+    assert_eq!(Balances::reserved_balance(2), 42);
+
+    // The deposit follows the claim to its new owner.
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(1), hash, 3));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::free_balance(1), 88);
+    assert_eq!(Balances::reserved_balance(3), 12);
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((3, 12)));
+
+    // And is returned to that owner on revoke.
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(3), hash));
+    assert_eq!(Balances::reserved_balance(3), 0);
+    assert_eq!(Balances::free_balance(3), 112);
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), None);
+	});
+}
+
+#[test]
+fn test_claim_deposit_required() {
+	new_test_ext().execute_with(|| {
+    assert!(PoeModule::create_claim(Origin::signed(4), vec![0, 1], None).is_err());
+    assert!(!Proofs::<Test>::contains_key(&digest(&[0, 1])));
+    assert_eq!(Balances::free_balance(4), 5);
+	});
+}
+
+#[test]
+fn test_direct_transfer_can_be_disabled() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    ALLOW_DIRECT_TRANSFER.with(|v| *v.borrow_mut() = false);
+    assert_noop!(
+      PoeModule::transfer_claim(Origin::signed(1), hash, 2),
+      Error::<Test>::DirectTransferDisabled
+    );
+    ALLOW_DIRECT_TRANSFER.with(|v| *v.borrow_mut() = true);
+	});
+}
+
+#[test]
+fn test_offer_and_accept_claim() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+
+    assert_noop!(
+      PoeModule::offer_claim(Origin::signed(1), hash, 2),
+      Error::<Test>::ClaimNotExist
+    );
+
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    assert_noop!(
+      PoeModule::offer_claim(Origin::signed(2), hash, 3),
+      Error::<Test>::NotClaimOwner
+    );
+    assert_noop!(
+      PoeModule::accept_claim(Origin::signed(2), hash),
+      Error::<Test>::NoPendingOffer
+    );
+
+    assert_ok!(PoeModule::offer_claim(Origin::signed(1), hash, 2));
+    assert_eq!(Offers::<Test>::get(&hash), Some(2));
+
+    // Nothing moves until the receiver accepts.
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 1);
+    assert_noop!(
+      PoeModule::accept_claim(Origin::signed(3), hash),
+      Error::<Test>::NotOfferRecipient
+    );
+
+    assert_ok!(PoeModule::accept_claim(Origin::signed(2), hash));
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 2);
+    assert_eq!(Offers::<Test>::get(&hash), None);
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::reserved_balance(2), 12);
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), vec![hash]);
+	});
+}
+
+#[test]
+fn test_offer_works_without_direct_transfer() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    ALLOW_DIRECT_TRANSFER.with(|v| *v.borrow_mut() = false);
+    assert_ok!(PoeModule::offer_claim(Origin::signed(1), hash, 2));
+    assert_ok!(PoeModule::accept_claim(Origin::signed(2), hash));
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 2);
+    ALLOW_DIRECT_TRANSFER.with(|v| *v.borrow_mut() = true);
+	});
+}
+
+#[test]
+fn test_cancel_offer() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    assert_noop!(
+      PoeModule::cancel_offer(Origin::signed(1), hash),
+      Error::<Test>::NoPendingOffer
+    );
+
+    assert_ok!(PoeModule::offer_claim(Origin::signed(1), hash, 2));
+    assert_noop!(
+      PoeModule::cancel_offer(Origin::signed(2), hash),
+      Error::<Test>::NotClaimOwner
+    );
+
+    assert_ok!(PoeModule::cancel_offer(Origin::signed(1), hash));
+    assert_noop!(
+      PoeModule::accept_claim(Origin::signed(2), hash),
+      Error::<Test>::NoPendingOffer
+    );
+
+    // Revoking the claim drops a pending offer as well.
+    assert_ok!(PoeModule::offer_claim(Origin::signed(1), hash, 2));
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), hash));
+    assert_eq!(Offers::<Test>::get(&hash), None);
+	});
+}
+
+#[test]
+fn test_merkle_tree_proofs() {
+  let documents: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 100]).collect();
+  let tree = merkle::MerkleTree::<Blake2Hasher>::from_documents(&documents).unwrap();
+  assert_eq!(tree.leaf_count(), 5);
+
+  for (i, document) in documents.iter().enumerate() {
+    let proof = tree.proof(i).unwrap();
+    assert!(proof.len() <= merkle::max_proof_len(5));
+    assert!(merkle::verify_inclusion::<Blake2Hasher>(&digest(document), &proof, &tree.root()));
+    // A proof does not carry over to another document.
+    assert!(!merkle::verify_inclusion::<Blake2Hasher>(&digest(&[9]), &proof, &tree.root()));
+  }
+  assert_eq!(tree.proof(5), None);
+
+  // A single document's leaf node is the root.
+  let single = merkle::MerkleTree::<Blake2Hasher>::from_documents(&[vec![1u8]]).unwrap();
+  assert_eq!(single.root(), merkle::hash_leaf::<Blake2Hasher>(&digest(&[1])));
+  assert_eq!(single.proof(0), Some(vec![]));
+
+  assert!(merkle::MerkleTree::<Blake2Hasher>::from_leaves(vec![]).is_none());
+}
+
+#[test]
+fn test_inner_node_is_not_a_leaf() {
+	new_test_ext().execute_with(|| {
+    let documents: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 100]).collect();
+    let tree = merkle::MerkleTree::<Blake2Hasher>::from_documents(&documents).unwrap();
+    let root = tree.root();
+    assert_ok!(PoeModule::create_batch_claim(Origin::signed(1), root, tree.leaf_count()));
+
+    // A forged document made of the tag and the two leaf nodes under an inner node
+    // has that inner node as its digest.
+    let left = merkle::hash_leaf::<Blake2Hasher>(&digest(&documents[0]));
+    let right = merkle::hash_leaf::<Blake2Hasher>(&digest(&documents[1]));
+    let (first, second) = if left <= right { (left, right) } else { (right, left) };
+    let mut forged = vec![1u8];
+    forged.extend_from_slice(&first);
+    forged.extend_from_slice(&second);
+    assert_eq!(digest(&forged), merkle::hash_pair::<Blake2Hasher>(&left, &right));
+
+    // The sibling of that inner node is the last step of the proof of document 0.
+    let sibling = *tree.proof(0).unwrap().last().unwrap();
+    assert!(!merkle::verify_inclusion::<Blake2Hasher>(&digest(&forged), &[sibling], &root));
+    assert_eq!(PoeModule::verify_inclusion(&digest(&forged), &[sibling], &root), None);
+	});
+}
+
+#[test]
+fn test_batch_claim() {
+	new_test_ext().execute_with(|| {
+    let documents: Vec<Vec<u8>> = (0..3u8).map(|i| vec![i; 100]).collect();
+    let tree = merkle::MerkleTree::<Blake2Hasher>::from_documents(&documents).unwrap();
+    let root = tree.root();
+
+    assert_noop!(
+      PoeModule::create_batch_claim(Origin::signed(1), root, 0),
+      Error::<Test>::EmptyBatch
+    );
+    assert_ok!(PoeModule::create_batch_claim(Origin::signed(1), root, tree.leaf_count()));
+    assert_eq!(BatchLeafCount::<Test>::get(&root), Some(3));
+
+    let proof = tree.proof(2).unwrap();
+    assert_eq!(
+      PoeModule::verify_inclusion(&digest(&documents[2]), &proof, &root),
+      Some((1, frame_system::Pallet::<Test>::block_number()))
+    );
+    assert_eq!(PoeModule::verify_inclusion(&digest(&[7]), &proof, &root), None);
+
+    // A plain claim is not a batch root, even if the digests line up.
+    let other = merkle::MerkleTree::<Blake2Hasher>::from_documents(&documents[..2]).unwrap();
+    assert_ok!(PoeModule::create_claim_for_hash(Origin::signed(2), other.root(), None));
+    assert_eq!(
+      PoeModule::verify_inclusion(&digest(&documents[0]), &other.proof(0).unwrap(), &other.root()),
+      None
+    );
+
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), root));
+    assert_eq!(BatchLeafCount::<Test>::get(&root), None);
+    assert_eq!(PoeModule::verify_inclusion(&digest(&documents[2]), &proof, &root), None);
+	});
+}
+
+#[test]
+fn test_claim_history() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    let record = |owner, from_block, to_block, reason| CustodyRecord { owner, from_block, to_block, reason };
+
+    run_to_block(1);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    run_to_block(2);
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(1), hash, 2));
+    run_to_block(3);
+    assert_ok!(PoeModule::offer_claim(Origin::signed(2), hash, 3));
+    assert_ok!(PoeModule::accept_claim(Origin::signed(3), hash));
+
+    assert_eq!(PoeModule::claim_history(&hash), vec![
+      record(1, 1, Some(2), CustodyReason::Created),
+      record(2, 2, Some(3), CustodyReason::Transferred),
+      record(3, 3, None, CustodyReason::OfferAccepted),
+    ]);
+
+    // `MaxHistoryLen` is 3, the oldest record makes room for the next one.
+    run_to_block(4);
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(3), hash, 1));
+    assert_eq!(PoeModule::claim_history(&hash), vec![
+      record(2, 2, Some(3), CustodyReason::Transferred),
+      record(3, 3, Some(4), CustodyReason::OfferAccepted),
+      record(1, 4, None, CustodyReason::Transferred),
+    ]);
+
+    // Revoking drops the history with the claim.
+    run_to_block(5);
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), hash));
+    assert!(PoeModule::claim_history(&hash).is_empty());
+	});
+}
+
+#[test]
+fn test_full_history_does_not_block_new_claims() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+
+    // Cycling the digest through more claims than `MaxHistoryLen` cannot lock it.
+    for _ in 0..5 {
+      assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![0, 1], None));
+      assert_ok!(PoeModule::revoke_claim(Origin::signed(2), hash));
+    }
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    assert_eq!(stored(&hash).0, 1);
+    assert_eq!(PoeModule::claim_history(&hash).len(), 1);
+
+    // Nor does a live claim that has changed hands more often than that.
+    for (from, to) in [(1, 2), (2, 3), (3, 1), (1, 2)].iter() {
+      assert_ok!(PoeModule::transfer_claim(Origin::signed(*from), hash, *to));
+    }
+    assert_eq!(PoeModule::claim_history(&hash).len(), 3);
+    assert_eq!(stored(&hash).0, 2);
+	});
+}
+
+#[test]
+fn test_claims_of() {
+	new_test_ext().execute_with(|| {
+    let (a, b, c) = (digest(&[0]), digest(&[1]), digest(&[2]));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0], None));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![1], None));
+    assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![2], None));
+
+    let mut owned = PoeModule::claims_of(&1, 0, 10);
+    owned.sort();
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(owned, expected);
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), vec![c]);
+
+    // Paging walks the same order.
+    let first = PoeModule::claims_of(&1, 0, 1);
+    let second = PoeModule::claims_of(&1, 1, 1);
+    assert_eq!(first.len(), 1);
+    assert_eq!([first, second].concat(), PoeModule::claims_of(&1, 0, 10));
+    assert!(PoeModule::claims_of(&1, 2, 10).is_empty());
+
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(1), a, 2));
+    assert_eq!(PoeModule::claims_of(&1, 0, 10), vec![b]);
+    assert_eq!(PoeModule::claims_of(&2, 0, 10).len(), 2);
+
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), b));
+    assert!(PoeModule::claims_of(&1, 0, 10).is_empty());
+	});
+}
+
+#[test]
+fn test_claim_expires() {
+	new_test_ext().execute_with(|| {
+    run_to_block(1);
+    let hash = digest(&[0, 1]);
+
+    assert_noop!(
+      PoeModule::create_claim(Origin::signed(1), vec![0, 1], Some(1)),
+      Error::<Test>::InvalidExpiry
+    );
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], Some(3)));
+
+    run_to_block(2);
+    assert_eq!(PoeModule::claim_of(&hash), Some((1, 1)));
+
+    run_to_block(3);
+    assert_eq!(PoeModule::claim_of(&hash), None);
+    assert!(!Proofs::<Test>::contains_key(&hash));
+    assert_eq!(ClaimExpiry::<Test>::get(&hash), None);
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert!(PoeModule::claims_of(&1, 0, 10).is_empty());
+    assert!(System::events().iter().any(|r| r.event == Event::pallet_poe(crate::Event::ClaimExpired(1, hash))));
+
+    // The proof can be claimed again once it has expired.
+    assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![0, 1], None));
+	});
+}
+
+#[test]
+fn test_expiry_sweep_is_bounded() {
+	new_test_ext().execute_with(|| {
+    run_to_block(1);
+    for i in 0..5u8 {
+      assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![i], Some(2)));
+    }
+
+    // `MaxExpiriesPerBlock` is 2, the rest spills over into the next blocks.
+    run_to_block(2);
+    assert_eq!(Proofs::<Test>::iter().count(), 3);
+    run_to_block(3);
+    assert_eq!(Proofs::<Test>::iter().count(), 1);
+    run_to_block(4);
+    assert_eq!(Proofs::<Test>::iter().count(), 0);
+    assert_eq!(ExpiryQueue::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn test_unswept_expired_claim_is_absent() {
+	new_test_ext().execute_with(|| {
+    run_to_block(1);
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], Some(3)));
+    assert_ok!(PoeModule::offer_claim(Origin::signed(1), hash, 3));
+
+    // Past the expiry block, before the sweep ran.
+    System::set_block_number(3);
+    assert!(Proofs::<Test>::contains_key(&hash));
+
+    assert_noop!(PoeModule::transfer_claim(Origin::signed(1), hash, 2), Error::<Test>::ClaimNotExist);
+    assert_noop!(PoeModule::offer_claim(Origin::signed(1), hash, 2), Error::<Test>::ClaimNotExist);
+    assert_noop!(PoeModule::accept_claim(Origin::signed(3), hash), Error::<Test>::ClaimNotExist);
+    assert_noop!(PoeModule::renew_claim(Origin::signed(1), hash, Some(10)), Error::<Test>::ClaimNotExist);
+    assert_noop!(
+      PoeModule::dispute_claim(Origin::signed(2), hash, digest(&[9])),
+      Error::<Test>::ClaimNotExist
+    );
+
+    // Claiming it again removes the expired claim first.
+    assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![0, 1], None));
+    assert_eq!(stored(&hash), (2, 3));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert!(PoeModule::claims_of(&1, 0, 10).is_empty());
+    assert_eq!(ClaimExpiry::<Test>::get(&hash), None);
+    assert!(System::events().iter().any(|r| r.event == Event::pallet_poe(crate::Event::ClaimExpired(1, hash))));
+	});
+}
+
+#[test]
+fn test_renew_claim() {
+	new_test_ext().execute_with(|| {
+    run_to_block(1);
+    let hash = digest(&[0, 1]);
+
+    assert_noop!(
+      PoeModule::renew_claim(Origin::signed(1), hash, Some(5)),
+      Error::<Test>::ClaimNotExist
+    );
+
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], Some(2)));
+
+    assert_noop!(
+      PoeModule::renew_claim(Origin::signed(2), hash, Some(5)),
       Error::<Test>::NotClaimOwner
     );
-		
+    assert_noop!(
+      PoeModule::renew_claim(Origin::signed(1), hash, Some(1)),
+      Error::<Test>::InvalidExpiry
+    );
+
+    assert_ok!(PoeModule::renew_claim(Origin::signed(1), hash, Some(5)));
+    assert_eq!(ClaimExpiry::<Test>::get(&hash), Some(5));
+
+    run_to_block(4);
+    assert!(Proofs::<Test>::contains_key(&hash));
+
+    // Dropping the expiry keeps the claim for good.
+    assert_ok!(PoeModule::renew_claim(Origin::signed(1), hash, None));
+    run_to_block(6);
+    assert_eq!(PoeModule::claim_of(&hash), Some((1, 1)));
+	});
+}
+
+#[test]
+fn test_migrate_raw_claims_to_digests() {
+	new_test_ext().execute_with(|| {
+    StorageVersion::<Test>::put(Releases::V1_0_0);
+
+    // Write two claims the way the raw-byte layout stored them.
+    let claims: Vec<(Vec<u8>, (u64, u64))> = vec![(vec![0, 1], (1, 3)), (vec![2, 3, 4], (2, 5))];
+    for (proof, claim) in claims.iter() {
+      frame_support::storage::migration::put_storage_value(
+        b"PoeModule",
+        b"Proofs",
+        &Blake2_128Concat::hash(&proof.encode()),
+        claim,
+      );
+    }
+
+    assert_eq!(migrations::pre_upgrade_count::<Test>(), 2);
+    assert!(PoeModule::on_runtime_upgrade() > 0);
+    assert_eq!(migrations::post_upgrade_checks::<Test>(2), Ok(2));
+
+    // Both migrations run in one upgrade.
+    assert_eq!(StorageVersion::<Test>::get(), Releases::V3_0_0);
+    for (proof, claim) in claims.iter() {
+      assert_eq!(stored(&digest(proof)), *claim);
+      assert!(Proofs::<Test>::get(&digest(proof)).estimated);
+      assert_eq!(PoeModule::verify(proof), Some(*claim));
+    }
+    assert_eq!(Proofs::<Test>::iter().count(), 2);
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), vec![digest(&[2, 3, 4])]);
+    assert_eq!(
+      PoeModule::claim_history(&digest(&[0, 1])),
+      vec![CustodyRecord { owner: 1, from_block: 3, to_block: None, reason: CustodyReason::Migrated }]
+    );
+
+    // Running the upgrade again is a no-op.
+    assert_eq!(PoeModule::on_runtime_upgrade(), 0);
+	});
+}
+
+#[test]
+fn test_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+    System::set_block_number(2);
+    Timestamp::set_timestamp(12_345);
+    let hash = digest(&[0, 1]);
+
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    assert_eq!(
+      Proofs::<Test>::get(&hash),
+      Claim { owner: 1, block: 2, moment: 12_345, estimated: false }
+    );
+    assert!(System::events().iter().any(|r| r.event == Event::pallet_poe(crate::Event::ClaimCreated(1, hash, 12_345))));
+
+    // A transfer restarts the claim at the time of the transfer.
+    System::set_block_number(3);
+    Timestamp::set_timestamp(12_351);
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(1), hash, 2));
+    assert_eq!(
+      Proofs::<Test>::get(&hash),
+      Claim { owner: 2, block: 3, moment: 12_351, estimated: false }
+    );
+	});
+}
+
+#[test]
+fn test_migrate_claims_to_timestamps() {
+	new_test_ext().execute_with(|| {
+    StorageVersion::<Test>::put(Releases::V2_0_0);
+
+    // Write a claim the way the digest layout without timestamps stored it.
+    let hash = digest(&[0, 1]);
+    frame_support::storage::migration::put_storage_value(
+      b"PoeModule",
+      b"Proofs",
+      &Blake2_128Concat::hash(&hash.encode()),
+      (1u64, 10u64),
+    );
+    // The owner index already exists in that layout.
+    ClaimsOf::<Test>::insert(1, &hash, ());
+
+    assert!(PoeModule::on_runtime_upgrade() > 0);
+
+    assert_eq!(StorageVersion::<Test>::get(), Releases::V3_0_0);
+    // `GenesisMoment` plus ten blocks of `ExpectedBlockTime`.
+    assert_eq!(
+      Proofs::<Test>::get(&hash),
+      Claim { owner: 1, block: 10, moment: 1_060, estimated: true }
+    );
+    assert_eq!(PoeModule::on_runtime_upgrade(), 0);
+	});
+}
+
+#[test]
+fn test_dispute_upheld_slashes_challenger() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    let evidence = digest(&[9]);
+
+    assert_noop!(
+      PoeModule::dispute_claim(Origin::signed(2), hash, evidence),
+      Error::<Test>::ClaimNotExist
+    );
+
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    assert_noop!(
+      PoeModule::dispute_claim(Origin::signed(1), hash, evidence),
+      Error::<Test>::CannotDisputeOwnClaim
+    );
+    // The bond must be affordable.
+    assert!(PoeModule::dispute_claim(Origin::signed(4), hash, evidence).is_err());
+
+    assert_ok!(PoeModule::dispute_claim(Origin::signed(2), hash, evidence));
+    assert_eq!(Balances::reserved_balance(2), 20);
+    assert_noop!(
+      PoeModule::dispute_claim(Origin::signed(3), hash, evidence),
+      Error::<Test>::AlreadyDisputed
+    );
+
+    // The claim is locked until the dispute is resolved.
+    assert_noop!(PoeModule::revoke_claim(Origin::signed(1), hash), Error::<Test>::ClaimLocked);
+    assert_noop!(PoeModule::transfer_claim(Origin::signed(1), hash, 3), Error::<Test>::ClaimLocked);
+    assert_noop!(PoeModule::offer_claim(Origin::signed(1), hash, 3), Error::<Test>::ClaimLocked);
+    assert_noop!(PoeModule::renew_claim(Origin::signed(1), hash, Some(5)), Error::<Test>::ClaimLocked);
+
+    assert_noop!(
+      PoeModule::resolve_dispute(Origin::signed(1), hash, false),
+      sp_runtime::DispatchError::BadOrigin
+    );
+    assert_ok!(PoeModule::resolve_dispute(Origin::root(), hash, false));
+
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert_eq!(Balances::free_balance(2), 80);
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 1);
+    assert_noop!(
+      PoeModule::resolve_dispute(Origin::root(), hash, false),
+      Error::<Test>::NoDispute
+    );
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), hash));
+	});
+}
+
+#[test]
+fn test_dispute_reassigns_claim() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    assert_ok!(PoeModule::dispute_claim(Origin::signed(2), hash, digest(&[9])));
+
+    assert_ok!(PoeModule::resolve_dispute(Origin::root(), hash, true));
+
+    // The owner's deposit is slashed, the challenger's bond backs the claim now.
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_eq!(Balances::free_balance(1), 88);
+    assert_eq!(Balances::reserved_balance(2), 20);
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((2, 20)));
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 2);
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), vec![hash]);
+    assert_eq!(
+      PoeModule::claim_history(&hash).last().map(|record| record.reason),
+      Some(CustodyReason::Arbitration)
+    );
+
+    // Revoking returns the bond to the new owner.
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(2), hash));
+    assert_eq!(Balances::free_balance(2), 100);
+	});
+}
+
+#[test]
+fn test_lost_dispute_refunds_sponsor() {
+	new_test_ext().execute_with(|| {
+    let proof = vec![0, 1];
+    let hash = digest(&proof);
+    let signature = sp_runtime::testing::TestSignature(5, PoeModule::signed_claim_payload(&proof, 0));
+    assert_ok!(PoeModule::create_claim_signed(Origin::signed(2), proof, 5, signature, 0));
+    assert_ok!(PoeModule::dispute_claim(Origin::signed(3), hash, digest(&[9])));
+
+    assert_ok!(PoeModule::resolve_dispute(Origin::root(), hash, true));
+
+    // The sponsor only relayed the claim, its deposit is returned rather than slashed.
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert_eq!(Balances::free_balance(2), 100);
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((3, 20)));
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 3);
+	});
+}
+
+#[test]
+fn test_dispute_outlives_expiry() {
+	new_test_ext().execute_with(|| {
+    run_to_block(1);
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], Some(3)));
+    assert_ok!(PoeModule::dispute_claim(Origin::signed(2), hash, digest(&[9])));
+
+    // The sweep leaves the disputed claim to the arbitrator.
+    run_to_block(3);
+    assert!(Proofs::<Test>::contains_key(&hash));
+    assert_eq!(ExpiryQueue::<Test>::iter().count(), 0);
+    assert_eq!(PoeModule::claim_of(&hash), None);
+    assert_eq!(Balances::reserved_balance(2), 20);
+    assert_noop!(
+      PoeModule::create_claim(Origin::signed(3), vec![0, 1], None),
+      Error::<Test>::ProofAlreadyExist
+    );
+
+    // Once resolved, the claim expires.
+    assert_ok!(PoeModule::resolve_dispute(Origin::root(), hash, false));
+    assert_eq!(Balances::free_balance(2), 80);
+    assert!(!Proofs::<Test>::contains_key(&hash));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert!(System::events().iter().any(|r| r.event == Event::pallet_poe(crate::Event::ClaimExpired(1, hash))));
+	});
+}
+
+#[test]
+fn test_migrate_from_legacy_pallet() {
+	new_test_ext().execute_with(|| {
+    StorageVersion::<Test>::put(Releases::V1_0_0);
+
+    // Write claims the way the `decl_storage` pallet stored them under its own prefix,
+    // one of them longer than `MaxClaimLen`.
+    let claims: Vec<(Vec<u8>, (u64, u64), u64)> =
+      vec![(vec![0, 1], (1, 3), 12), (vec![7u8; 20], (2, 5), 30)];
+    for (proof, claim, deposit) in claims.iter() {
+      let key = Blake2_128Concat::hash(&proof.encode());
+      frame_support::storage::migration::put_storage_value(b"TemplateModule", b"Proofs", &key, claim);
+      frame_support::storage::migration::put_storage_value(b"TemplateModule", b"ClaimDeposits", &key, deposit);
+      assert_ok!(<Balances as frame_support::traits::ReservableCurrency<u64>>::reserve(&claim.0, *deposit));
+    }
+
+    assert_eq!(migrations::pre_upgrade_count::<Test>(), 2);
+    assert!(PoeModule::on_runtime_upgrade() > 0);
+    assert_eq!(migrations::post_upgrade_checks::<Test>(2), Ok(2));
+
+    for (proof, claim, deposit) in claims.iter() {
+      let hash = digest(proof);
+      assert_eq!(stored(&hash), *claim);
+      assert!(Proofs::<Test>::get(&hash).estimated);
+      assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((claim.0, *deposit)));
+      assert_eq!(PoeModule::verify(proof), Some(*claim));
+    }
+
+    // The deposit is returned as for any other claim.
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(1), digest(&[0, 1])));
+    assert_eq!(Balances::free_balance(1), 100);
+	});
+}
+
+#[test]
+fn test_migrate_from_legacy_skips_colliding_claims() {
+	new_test_ext().execute_with(|| {
+    StorageVersion::<Test>::put(Releases::V1_0_0);
+
+    // The same bytes are claimed by 1 in this pallet and by 2 in the legacy one.
+    let proof = vec![0u8, 1];
+    let key = Blake2_128Concat::hash(&proof.encode());
+    frame_support::storage::migration::put_storage_value(b"PoeModule", b"Proofs", &key, (1u64, 2u64));
+    frame_support::storage::migration::put_storage_value(b"TemplateModule", b"Proofs", &key, (2u64, 4u64));
+    frame_support::storage::migration::put_storage_value(b"TemplateModule", b"ClaimDeposits", &key, 12u64);
+    assert_ok!(<Balances as frame_support::traits::ReservableCurrency<u64>>::reserve(&2, 12));
+
+    // The claim of this pallet counts, the colliding legacy one does not.
+    assert_eq!(migrations::pre_upgrade_count::<Test>(), 1);
+    assert!(PoeModule::on_runtime_upgrade() > 0);
+
+    assert_eq!(stored(&digest(&proof)), (1, 2));
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), Vec::<ClaimHash>::new());
+    assert_eq!(Balances::reserved_balance(2), 0);
+    assert_eq!(migrations::post_upgrade_checks::<Test>(1), Ok(1));
+	});
+}
+
+fn genesis_ext(claims: Vec<(Vec<u8>, u64, u64)>) -> sp_io::TestExternalities {
+  let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+  frame_support::traits::GenesisBuild::<Test>::assimilate_storage(
+    &crate::GenesisConfig::<Test> { claims },
+    &mut t,
+  ).unwrap();
+  t.into()
+}
+
+#[test]
+fn test_genesis_claims() {
+  genesis_ext(vec![(vec![0, 1], 1, 0), (vec![2, 3], 2, 5)]).execute_with(|| {
+    assert_eq!(StorageVersion::<Test>::get(), Releases::V3_0_0);
+    assert_eq!(PoeModule::verify(&[0, 1]), Some((1, 0)));
+    assert_eq!(PoeModule::verify(&[2, 3]), Some((2, 5)));
+    // `GenesisMoment` plus five blocks of `ExpectedBlockTime`.
+    assert_eq!(
+      Proofs::<Test>::get(&digest(&[2, 3])),
+      Claim { owner: 2, block: 5, moment: 1_030, estimated: true }
+    );
+    assert_eq!(PoeModule::claims_of(&2, 0, 10), vec![digest(&[2, 3])]);
+    assert_eq!(
+      PoeModule::claim_history(&digest(&[0, 1])),
+      vec![CustodyRecord { owner: 1, from_block: 0, to_block: None, reason: CustodyReason::Genesis }]
+    );
+  });
+}
+
+#[test]
+#[should_panic(expected = "duplicate claim in genesis")]
+fn test_genesis_rejects_duplicates() {
+  genesis_ext(vec![(vec![0, 1], 1, 0), (vec![0, 1], 2, 0)]);
+}
+
+#[test]
+#[should_panic(expected = "genesis claim longer than MaxClaimLen")]
+fn test_genesis_rejects_long_claims() {
+  genesis_ext(vec![(vec![0; 11], 1, 0)]);
+}
+
+#[test]
+fn test_create_claim_signed() {
+	new_test_ext().execute_with(|| {
+    use sp_runtime::testing::TestSignature;
+    let proof = vec![0, 1];
+    let hash = digest(&proof);
+    let payload = PoeModule::signed_claim_payload(&proof, 0);
+
+    // A signature over the same fields without the tag is not accepted.
+    let untagged = (&proof, 0u64, System::block_hash(0)).encode();
+    assert_noop!(
+      PoeModule::create_claim_signed(Origin::signed(2), proof.clone(), 5, TestSignature(5, untagged), 0),
+      Error::<Test>::BadSignature
+    );
+
+    // Account 5 has no balance, account 2 relays the claim and pays the deposit.
+    assert_noop!(
+      PoeModule::create_claim_signed(Origin::signed(2), proof.clone(), 5, TestSignature(6, payload.clone()), 0),
+      Error::<Test>::BadSignature
+    );
+    assert_noop!(
+      PoeModule::create_claim_signed(Origin::signed(2), proof.clone(), 5, TestSignature(5, payload.clone()), 1),
+      Error::<Test>::InvalidNonce
+    );
+    assert_ok!(PoeModule::create_claim_signed(Origin::signed(2), proof.clone(), 5, TestSignature(5, payload.clone()), 0));
+
+    assert_eq!(stored(&hash), (5, 0));
+    assert_eq!(Nonces::<Test>::get(&5), 1);
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((2, 12)));
+    assert_eq!(Balances::reserved_balance(2), 12);
+
+    // The signature cannot be replayed.
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(5), hash));
+    assert_noop!(
+      PoeModule::create_claim_signed(Origin::signed(2), proof.clone(), 5, TestSignature(5, payload), 0),
+      Error::<Test>::InvalidNonce
+    );
+    assert_eq!(Balances::free_balance(2), 100);
+	});
+}
+
+#[test]
+fn test_sponsored_deposit_stays_with_sponsor() {
+	new_test_ext().execute_with(|| {
+    let proof = vec![0, 1];
+    let hash = digest(&proof);
+    let signature = sp_runtime::testing::TestSignature(5, PoeModule::signed_claim_payload(&proof, 0));
+    assert_ok!(PoeModule::create_claim_signed(Origin::signed(2), proof, 5, signature, 0));
+
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(5), hash, 3));
+    assert_eq!(ClaimDeposits::<Test>::get(&hash), Some((2, 12)));
+    assert_eq!(Balances::reserved_balance(3), 0);
+
+    assert_ok!(PoeModule::revoke_claim(Origin::signed(3), hash));
+    assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn test_collections() {
+	new_test_ext().execute_with(|| {
+    let proof = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+    let hash = digest(&proof);
+
+    assert_noop!(
+      PoeModule::create_collection_claim(Origin::signed(1), 0, proof.clone()),
+      Error::<Test>::UnknownCollection
+    );
+
+    // Collection 0 takes longer claims than `MaxClaimLen`, collection 1 uses the default.
+    assert_ok!(PoeModule::create_collection(Origin::signed(1), 1, Some(20)));
+    assert_ok!(PoeModule::create_collection(Origin::signed(2), 2, None));
+    assert_eq!(Balances::reserved_balance(1), 10);
+    assert_noop!(
+      PoeModule::create_collection_claim(Origin::signed(3), 1, proof.clone()),
+      Error::<Test>::InvalidClaimLength
+    );
+
+    // Each collection is a registry of its own.
+    assert_ok!(PoeModule::create_collection_claim(Origin::signed(3), 0, proof.clone()));
+    assert_noop!(
+      PoeModule::create_collection_claim(Origin::signed(2), 0, proof.clone()),
+      Error::<Test>::ProofAlreadyExist
+    );
+    assert_ok!(PoeModule::create_collection_claim(Origin::signed(2), 1, vec![0, 1]));
+    assert_ok!(PoeModule::create_collection_claim(Origin::signed(1), 1, vec![2, 3]));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    assert_eq!(PoeModule::collection_claim_of(0, &hash), Some((3, 0)));
+    assert_eq!(PoeModule::collection_claim_of(1, &digest(&[0, 1])), Some((2, 0)));
+    assert_eq!(PoeModule::verify(&[0, 1]), Some((1, 0)));
+    assert_eq!(Balances::reserved_balance(3), 22);
+
+    // The admin of collection 0 can revoke any of its claims, no one else can.
+    assert_noop!(
+      PoeModule::revoke_collection_claim(Origin::signed(2), 0, hash),
+      Error::<Test>::NotClaimOwner
+    );
+    assert_ok!(PoeModule::revoke_collection_claim(Origin::signed(1), 0, hash));
+    assert_eq!(PoeModule::collection_claim_of(0, &hash), None);
+    assert_eq!(Balances::reserved_balance(3), 0);
+
+    // Owners can revoke their own claims.
+    assert_ok!(PoeModule::revoke_collection_claim(Origin::signed(1), 1, digest(&[2, 3])));
+    assert_noop!(
+      PoeModule::revoke_collection_claim(Origin::signed(1), 1, digest(&[2, 3])),
+      Error::<Test>::ClaimNotExist
+    );
+	});
+}
+
+#[test]
+fn test_collection_deposit_and_limits() {
+	new_test_ext().execute_with(|| {
+    // Account 4 cannot afford the collection deposit.
+    assert!(PoeModule::create_collection(Origin::signed(4), 4, None).is_err());
+    assert!(Collections::<Test>::get(0).is_none());
+
+    // The claim length limit of a collection is capped.
+    assert_noop!(
+      PoeModule::create_collection(Origin::signed(1), 1, Some(21)),
+      Error::<Test>::CollectionClaimLenTooHigh
+    );
+
+    assert_ok!(PoeModule::create_collection(Origin::signed(1), 2, Some(20)));
+    assert_eq!(Balances::reserved_balance(1), 10);
+    assert_ok!(PoeModule::create_collection_claim(Origin::signed(3), 0, vec![0, 1]));
+
+    // Only the creator can destroy the collection, once it is empty.
+    assert_noop!(
+      PoeModule::destroy_collection(Origin::signed(2), 0),
+      Error::<Test>::NotCollectionOwner
+    );
+    assert_noop!(
+      PoeModule::destroy_collection(Origin::signed(1), 0),
+      Error::<Test>::CollectionNotEmpty
+    );
+    assert_ok!(PoeModule::revoke_collection_claim(Origin::signed(2), 0, digest(&[0, 1])));
+    assert_ok!(PoeModule::destroy_collection(Origin::signed(1), 0));
+    assert_eq!(Balances::reserved_balance(1), 0);
+    assert_noop!(
+      PoeModule::create_collection_claim(Origin::signed(3), 0, vec![0, 1]),
+      Error::<Test>::UnknownCollection
+    );
+	});
+}
+
+#[test]
+fn test_operators() {
+	new_test_ext().execute_with(|| {
+    // Events are only recorded from block 1 on.
+    System::set_block_number(1);
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+
+    assert_noop!(
+      PoeModule::transfer_claim(Origin::signed(2), hash, 3),
+      Error::<Test>::NotClaimOwner
+    );
+    assert_noop!(PoeModule::revoke_operator(Origin::signed(1), 2), Error::<Test>::NotOperator);
+
+    // An operator acts for the owner, and the events record it.
+    assert_ok!(PoeModule::approve_operator(Origin::signed(1), 2));
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(2), hash, 3));
+    assert_eq!(Proofs::<Test>::get(&hash).owner, 3);
+    assert!(System::events().iter().any(|r| r.event == Event::pallet_poe(crate::Event::OperatorActed(2, 1, hash))));
+
+    // The approval is for the claims of account 1 only.
+    assert_noop!(PoeModule::revoke_claim(Origin::signed(2), hash), Error::<Test>::NotClaimOwner);
+
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![2, 3], None));
+    assert_ok!(PoeModule::revoke_operator(Origin::signed(1), 2));
+    assert_noop!(
+      PoeModule::revoke_claim(Origin::signed(2), digest(&[2, 3])),
+      Error::<Test>::NotClaimOwner
+    );
+	});
+}
+
+#[test]
+fn test_claim_operator() {
+	new_test_ext().execute_with(|| {
+    let hash = digest(&[0, 1]);
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1], None));
+    assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![2, 3], None));
+
+    // Only the owner approves, operators cannot pass their rights on.
+    assert_noop!(
+      PoeModule::set_claim_operator(Origin::signed(2), hash, Some(2)),
+      Error::<Test>::NotClaimOwner
+    );
+    assert_ok!(PoeModule::set_claim_operator(Origin::signed(1), hash, Some(2)));
+
+    // The approval covers this claim only.
+    assert_noop!(
+      PoeModule::revoke_claim(Origin::signed(2), digest(&[2, 3])),
+      Error::<Test>::NotClaimOwner
+    );
+
+    // It ends when the claim changes hands.
+    assert_ok!(PoeModule::transfer_claim(Origin::signed(2), hash, 3));
+    assert_eq!(ClaimOperators::<Test>::get(&hash), None);
+    assert_noop!(PoeModule::revoke_claim(Origin::signed(2), hash), Error::<Test>::NotClaimOwner);
+
+    // And can be cleared by the owner.
+    assert_ok!(PoeModule::set_claim_operator(Origin::signed(3), hash, Some(4)));
+    assert_ok!(PoeModule::set_claim_operator(Origin::signed(3), hash, None));
+    assert_noop!(PoeModule::revoke_claim(Origin::signed(4), hash), Error::<Test>::NotClaimOwner);
+	});
+}
+
+#[test]
+fn test_create_claims_all_or_nothing() {
+	new_test_ext().execute_with(|| {
+    assert_noop!(
+      PoeModule::create_claims(Origin::signed(1), vec![vec![0]; 4], BatchMode::AllOrNothing),
+      Error::<Test>::BatchTooLarge
+    );
+
+    // The duplicate fails the batch, the first claim is rolled back with it.
+    assert_noop!(
+      PoeModule::create_claims(Origin::signed(1), vec![vec![0, 1], vec![2, 3], vec![0, 1]], BatchMode::AllOrNothing),
+      Error::<Test>::ProofAlreadyExist
+    );
+
+    assert_ok!(PoeModule::create_claims(Origin::signed(1), vec![vec![0, 1], vec![2, 3]], BatchMode::AllOrNothing));
+    assert_eq!(PoeModule::verify(&[0, 1]), Some((1, 0)));
+    assert_eq!(PoeModule::verify(&[2, 3]), Some((1, 0)));
+    assert_eq!(Balances::reserved_balance(1), 24);
+
+    // Revoking fails as a whole on a claim of another account.
+    assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![4, 5], None));
+    let hashes = vec![digest(&[0, 1]), digest(&[4, 5])];
+    assert_noop!(
+      PoeModule::revoke_claims(Origin::signed(1), hashes, BatchMode::AllOrNothing),
+      Error::<Test>::NotClaimOwner
+    );
+    assert_ok!(PoeModule::revoke_claims(
+      Origin::signed(1),
+      vec![digest(&[0, 1]), digest(&[2, 3])],
+      BatchMode::AllOrNothing
+    ));
+    assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn test_claims_best_effort() {
+	new_test_ext().execute_with(|| {
+    System::set_block_number(1);
+    assert_ok!(PoeModule::create_claim(Origin::signed(2), vec![0, 1], None));
+
+    let long = vec![0; 11];
+    assert_ok!(PoeModule::create_claims(
+      Origin::signed(1),
+      vec![vec![2, 3], vec![0, 1], long.clone()],
+      BatchMode::BestEffort
+    ));
+
+    assert_eq!(PoeModule::verify(&[2, 3]), Some((1, 1)));
+    assert_eq!(PoeModule::verify(&[0, 1]), Some((2, 1)));
+    let results: Vec<_> = System::events()
+      .into_iter()
+      .filter_map(|r| match r.event {
+        Event::pallet_poe(crate::Event::BatchItemResult(index, hash, result)) => Some((index, hash, result)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(results, vec![
+      (0, digest(&[2, 3]), Ok(())),
+      (1, digest(&[0, 1]), Err(Error::<Test>::ProofAlreadyExist.into())),
+      (2, digest(&long), Err(Error::<Test>::InvalidClaimLength.into())),
+    ]);
+
+    // Revoking skips the claim of account 2.
+    assert_ok!(PoeModule::revoke_claims(
+      Origin::signed(1),
+      vec![digest(&[0, 1]), digest(&[2, 3])],
+      BatchMode::BestEffort
+    ));
+    assert_eq!(PoeModule::verify(&[0, 1]), Some((2, 1)));
+    assert_eq!(PoeModule::verify(&[2, 3]), None);
 	});
 }
@@ -0,0 +1,78 @@
+//! Check a `poe_claimProof` response against a block header, offline.
+//!
+//! Usage: `poe-verify <header.json> <claim-proof.json> <document | 0x-digest> [pallet-name]`
+//!
+//! `header.json` is the result of `chain_getHeader` for the block of the proof,
+//! taken from a source you trust, and `claim-proof.json` the result of
+//! `poe_claimProof`. The claim to check is given by the document itself, hashed with
+//! Blake2-256, or by its digest in hex; the proof must be for that claim.
+//!
+//! Claims are decoded as `Claim<AccountId32, u32, u64>`, the types of the node
+//! template runtime: proofs from a runtime with other account, block number or
+//! moment types are rejected as undecodable.
+
+use std::{env, fs, process};
+
+use pallet_poe::{Blake2Hasher, Claim, ClaimHash, ContentHasher};
+use pallet_poe_verifier::{verify_claim_proof, BlockNumber, ClaimProof, Error, Header, DEFAULT_PALLET_NAME};
+use sp_core::{bytes::from_hex, crypto::AccountId32, H256};
+
+const USAGE: &str = "usage: poe-verify <header.json> <claim-proof.json> <document | 0x-digest> [pallet-name]\n\
+	claims are decoded with the node template types: AccountId32 owners, u32 blocks, u64 moments";
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+	let content = fs::read(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+	serde_json::from_slice(&content).map_err(|e| format!("cannot parse {}: {}", path, e))
+}
+
+/// The digest of the claim to check: `0x` and the hex digest, or the path of the
+/// document, hashed with Blake2-256 as the node template runtime does.
+fn claim_hash(arg: &str) -> Result<ClaimHash, String> {
+	if !arg.starts_with("0x") {
+		let document = fs::read(arg).map_err(|e| format!("cannot read {}: {}", arg, e))?;
+		return Ok(Blake2Hasher::hash(&document));
+	}
+	let bytes = from_hex(arg).map_err(|e| format!("invalid digest {}: {:?}", arg, e))?;
+	let mut hash = [0u8; 32];
+	if bytes.len() != hash.len() {
+		return Err(format!("invalid digest {}: expected 32 bytes", arg));
+	}
+	hash.copy_from_slice(&bytes);
+	Ok(hash)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+	let (header_path, proof_path, claim) = match args {
+		[header, proof, claim] | [header, proof, claim, _] => (header, proof, claim),
+		_ => return Err(USAGE.into()),
+	};
+	let pallet_name = args.get(3).map(String::as_str).unwrap_or(DEFAULT_PALLET_NAME);
+
+	let header: Header = read_json(header_path)?;
+	let claim_proof: ClaimProof<H256, AccountId32, BlockNumber> = read_json(proof_path)?;
+	let hash = claim_hash(claim)?;
+
+	match verify_claim_proof::<AccountId32, u64>(&header, pallet_name, &hash, &claim_proof) {
+		Ok(Some(Claim { owner, block, moment, estimated })) => {
+			println!("claim {:?} exists at block {:?}", H256::from(hash), header.hash());
+			println!("owner: {}", owner);
+			println!("since block: {}", block);
+			println!("timestamp: {}{}", moment, if estimated { " (estimated)" } else { "" });
+			Ok(())
+		}
+		Ok(None) => {
+			println!("no unexpired claim {:?} at block {:?}", H256::from(hash), header.hash());
+			Ok(())
+		}
+		Err(Error::KeyMismatch) => Err(format!("the proof is not for claim {:?}", H256::from(hash))),
+		Err(e) => Err(format!("invalid claim proof: {:?}", e)),
+	}
+}
+
+fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+	if let Err(e) = run(&args) {
+		eprintln!("{}", e);
+		process::exit(1);
+	}
+}
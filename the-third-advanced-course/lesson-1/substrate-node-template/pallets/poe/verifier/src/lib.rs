@@ -0,0 +1,213 @@
+//! Offline verification of the claim proofs returned by the `poe_claimProof` RPC.
+//!
+//! A claim proof is a storage read proof of the `Proofs` and `ClaimExpiry` entries of
+//! a claim. Checked against the state root of a block header obtained from a trusted
+//! source, it shows whether the claim existed at that block without trusting the node
+//! that served it.
+
+use codec::Decode;
+use pallet_poe::{Claim, ClaimHash};
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	hashing::{blake2_128, twox_128},
+	Bytes,
+};
+use sp_runtime::{
+	generic,
+	traits::{BlakeTwo256, Header as HeaderT},
+};
+use sp_state_machine::{read_proof_check, StorageProof};
+
+/// The block number of the node template.
+pub type BlockNumber = u32;
+
+/// The header of a node template block.
+pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
+
+/// The name the PoE pallet has in the node template runtime.
+pub const DEFAULT_PALLET_NAME: &str = "PoeModule";
+
+/// A claim and the storage read proof of its `Proofs` and `ClaimExpiry` entries at
+/// block `at`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimProof<BlockHash, AccountId, BlockNumber> {
+	/// The block the proof was read at.
+	pub at: BlockHash,
+	/// The owner and block of the claim as reported by the node, not part of the proof.
+	pub claim: Option<(AccountId, BlockNumber)>,
+	/// The storage key of the claim.
+	pub key: Bytes,
+	/// The storage key of the expiry of the claim.
+	pub expiry_key: Bytes,
+	/// The trie nodes proving the values, or the absence, of `key` and `expiry_key`.
+	pub proof: Vec<Bytes>,
+}
+
+/// Why a claim proof was rejected.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+	/// The proof was read at another block than the one of the header.
+	BlockMismatch,
+	/// The proof is for other storage keys than those of the claim.
+	KeyMismatch,
+	/// The proof does not match the state root of the header.
+	InvalidProof(String),
+	/// The proven value is not a claim or an expiry.
+	Undecodable,
+}
+
+/// The key of the entry on `hash` of the `Blake2_128Concat` map `storage_name` in a
+/// pallet named `pallet_name`.
+fn map_key(pallet_name: &str, storage_name: &[u8], hash: &ClaimHash) -> Vec<u8> {
+	let mut key = twox_128(pallet_name.as_bytes()).to_vec();
+	key.extend_from_slice(&twox_128(storage_name));
+	key.extend_from_slice(&blake2_128(hash));
+	key.extend_from_slice(hash);
+	key
+}
+
+/// The storage key of the claim on `hash` in a pallet named `pallet_name`.
+pub fn claim_key(pallet_name: &str, hash: &ClaimHash) -> Vec<u8> {
+	map_key(pallet_name, b"Proofs", hash)
+}
+
+/// The storage key of the expiry of the claim on `hash` in a pallet named `pallet_name`.
+pub fn claim_expiry_key(pallet_name: &str, hash: &ClaimHash) -> Vec<u8> {
+	map_key(pallet_name, b"ClaimExpiry", hash)
+}
+
+/// Check `claim_proof` for the claim on `hash` against `header`, and return the
+/// proven claim, or `None` if the proof shows there is no claim on `hash` or that it
+/// expired by the block of `header`.
+///
+/// Expired claims stay in `Proofs` until they are swept, so the expiry is proven too.
+///
+/// `header` must come from a source trusted to follow the finalized chain.
+pub fn verify_claim_proof<AccountId: Decode, Moment: Decode>(
+	header: &Header,
+	pallet_name: &str,
+	hash: &ClaimHash,
+	claim_proof: &ClaimProof<<Header as HeaderT>::Hash, AccountId, BlockNumber>,
+) -> Result<Option<Claim<AccountId, BlockNumber, Moment>>, Error> {
+	if header.hash() != claim_proof.at {
+		return Err(Error::BlockMismatch);
+	}
+	let key = claim_key(pallet_name, hash);
+	let expiry_key = claim_expiry_key(pallet_name, hash);
+	if claim_proof.key.0 != key || claim_proof.expiry_key.0 != expiry_key {
+		return Err(Error::KeyMismatch);
+	}
+
+	let proof = StorageProof::new(claim_proof.proof.iter().map(|node| node.0.clone()).collect());
+	let mut values = read_proof_check::<BlakeTwo256, _>(*header.state_root(), proof, &[&key, &expiry_key])
+		.map_err(|e| Error::InvalidProof(e.to_string()))?;
+
+	if let Some(value) = values.remove(&expiry_key).flatten() {
+		let expires_at = BlockNumber::decode(&mut &value[..]).map_err(|_| Error::Undecodable)?;
+		if expires_at <= *header.number() {
+			return Ok(None);
+		}
+	}
+
+	match values.remove(&key).flatten() {
+		Some(value) => Claim::decode(&mut &value[..]).map(Some).map_err(|_| Error::Undecodable),
+		None => Ok(None),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_core::H256;
+	use sp_state_machine::{prove_read, InMemoryBackend};
+
+	type TestClaim = Claim<u64, u32, u64>;
+
+	/// A header of block 7 whose state holds `claim` on `hash`, expiring at `expiry`,
+	/// and the proof of it.
+	fn prove(
+		hash: &ClaimHash,
+		claim: &TestClaim,
+		expiry: Option<BlockNumber>,
+	) -> (Header, ClaimProof<H256, u64, u32>) {
+		let key = claim_key(DEFAULT_PALLET_NAME, hash);
+		let expiry_key = claim_expiry_key(DEFAULT_PALLET_NAME, hash);
+		let backend: InMemoryBackend<BlakeTwo256> = vec![(None, vec![
+			(key.clone(), Some(claim.encode())),
+			(expiry_key.clone(), expiry.map(|e| e.encode())),
+		])].into();
+		let state_root = *backend.root();
+		let proof = prove_read(backend, &[&key, &expiry_key]).unwrap();
+
+		let header = Header::new(7, Default::default(), state_root, Default::default(), Default::default());
+		let claim_proof = ClaimProof {
+			at: header.hash(),
+			claim: Some((claim.owner, claim.block)),
+			key: key.into(),
+			expiry_key: expiry_key.into(),
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		};
+		(header, claim_proof)
+	}
+
+	#[test]
+	fn verifies_claim_proofs() {
+		let hash = [1u8; 32];
+		let claim = TestClaim { owner: 1, block: 5, moment: 30_000, estimated: false };
+		let (header, claim_proof) = prove(&hash, &claim, None);
+
+		assert_eq!(
+			verify_claim_proof(&header, DEFAULT_PALLET_NAME, &hash, &claim_proof),
+			Ok(Some(claim.clone()))
+		);
+
+		// A proof for one claim says nothing about another.
+		assert_eq!(
+			verify_claim_proof::<u64, u64>(&header, DEFAULT_PALLET_NAME, &[2u8; 32], &claim_proof),
+			Err(Error::KeyMismatch)
+		);
+
+		// Nor does it hold for another block.
+		let mut other = header.clone();
+		other.number = 8;
+		assert_eq!(
+			verify_claim_proof::<u64, u64>(&other, DEFAULT_PALLET_NAME, &hash, &claim_proof),
+			Err(Error::BlockMismatch)
+		);
+
+		// Tampered proofs are rejected.
+		let mut tampered = claim_proof.clone();
+		tampered.proof.clear();
+		assert!(matches!(
+			verify_claim_proof::<u64, u64>(&header, DEFAULT_PALLET_NAME, &hash, &tampered),
+			Err(Error::InvalidProof(_))
+		));
+	}
+
+	#[test]
+	fn expired_claims_are_not_proven() {
+		let hash = [1u8; 32];
+		let claim = TestClaim { owner: 1, block: 5, moment: 30_000, estimated: false };
+
+		// The claim expires after the block of the header.
+		let (header, claim_proof) = prove(&hash, &claim, Some(8));
+		assert_eq!(
+			verify_claim_proof(&header, DEFAULT_PALLET_NAME, &hash, &claim_proof),
+			Ok(Some(claim.clone()))
+		);
+
+		// The claim expired at the block of the header but was not swept yet.
+		let (header, claim_proof) = prove(&hash, &claim, Some(7));
+		assert_eq!(verify_claim_proof::<u64, u64>(&header, DEFAULT_PALLET_NAME, &hash, &claim_proof), Ok(None));
+
+		// The expiry cannot be left out of the proof.
+		let mut tampered = claim_proof.clone();
+		tampered.expiry_key = claim_key(DEFAULT_PALLET_NAME, &hash).into();
+		assert_eq!(
+			verify_claim_proof::<u64, u64>(&header, DEFAULT_PALLET_NAME, &hash, &tampered),
+			Err(Error::KeyMismatch)
+		);
+	}
+}
@@ -39,8 +39,8 @@ pub use frame_support::{
 };
 use pallet_transaction_payment::CurrencyAdapter;
 
-/// Import the template pallet.
-pub use pallet_template;
+/// Import the proof of existence pallet.
+pub use pallet_poe;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -265,9 +265,49 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
-/// Configure the pallet-template in pallets/template.
-impl pallet_template::Config for Runtime {
+parameter_types! {
+	pub const MaxClaimLen: u32 = 256;
+	pub const MaxExpiriesPerBlock: u32 = 64;
+	pub const ClaimDepositBase: Balance = 10_000;
+	pub const ClaimDepositPerByte: Balance = 100;
+	/// Claims may still be moved with `transfer_claim`; set to `false` to require
+	/// the receiver to accept an offer.
+	pub const AllowDirectClaimTransfer: bool = true;
+	pub const MaxClaimHistoryLen: u32 = 100;
+	pub const ExpectedClaimBlockTime: u64 = MILLISECS_PER_BLOCK;
+	/// The time the chain started at, used to estimate the timestamp of claims made
+	/// before they were recorded. Set it to that of the live chain before upgrading it.
+	pub const ClaimGenesisMoment: u64 = 0;
+	pub const ClaimDisputeBond: Balance = 100_000;
+	pub const MaxClaimBatchSize: u32 = 100;
+	pub const ClaimCollectionDeposit: Balance = 1_000_000;
+	pub const MaxCollectionClaimLen: u32 = 4096;
+}
+
+/// Configure the pallet-poe in pallets/poe.
+impl pallet_poe::Config for Runtime {
 	type Event = Event;
+	type MaxClaimLen = MaxClaimLen;
+	type Hasher = pallet_poe::Blake2Hasher;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type Currency = Balances;
+	type DepositBase = ClaimDepositBase;
+	type DepositPerByte = ClaimDepositPerByte;
+	type MaxHistoryLen = MaxClaimHistoryLen;
+	type AllowDirectTransfer = AllowDirectClaimTransfer;
+	type Time = Timestamp;
+	type GenesisMoment = ClaimGenesisMoment;
+	type ExpectedBlockTime = ExpectedClaimBlockTime;
+	type DisputeBond = ClaimDisputeBond;
+	// Disputes are settled by sudo until there is a council.
+	type ArbitratorOrigin = frame_system::EnsureRoot<AccountId>;
+	type Slashed = ();
+	type MaxBatchSize = MaxClaimBatchSize;
+	type CollectionDeposit = ClaimCollectionDeposit;
+	type MaxCollectionClaimLen = MaxCollectionClaimLen;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type WeightInfo = pallet_poe::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -285,8 +325,8 @@ construct_runtime!(
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
-		// Include the custom logic from the pallet-template in the runtime.
-		TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
+		// Include the custom logic from the pallet-poe in the runtime.
+		PoeModule: pallet_poe::{Module, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
@@ -453,6 +493,49 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber> for Runtime {
+		fn verify(document: Vec<u8>) -> Option<(AccountId, BlockNumber)> {
+			PoeModule::verify(&document)
+		}
+
+		fn claim(hash: pallet_poe::ClaimHash) -> Option<(AccountId, BlockNumber)> {
+			PoeModule::claim_of(&hash)
+		}
+
+		fn claims_of(account: AccountId, start: u32, limit: u32) -> Vec<pallet_poe::ClaimHash> {
+			PoeModule::claims_of(&account, start, limit)
+		}
+
+		fn verify_inclusion(
+			leaf: pallet_poe::ClaimHash,
+			proof: Vec<pallet_poe::ClaimHash>,
+			root: pallet_poe::ClaimHash,
+		) -> Option<(AccountId, BlockNumber)> {
+			PoeModule::verify_inclusion(&leaf, &proof, &root)
+		}
+
+		fn claim_history(
+			hash: pallet_poe::ClaimHash,
+		) -> Vec<pallet_poe::CustodyRecord<AccountId, BlockNumber>> {
+			PoeModule::claim_history(&hash)
+		}
+
+		fn claim_key(hash: pallet_poe::ClaimHash) -> Vec<u8> {
+			PoeModule::claim_key(&hash)
+		}
+
+		fn claim_expiry_key(hash: pallet_poe::ClaimHash) -> Vec<u8> {
+			PoeModule::claim_expiry_key(&hash)
+		}
+
+		fn collection_claim(
+			collection: pallet_poe::CollectionId,
+			hash: pallet_poe::ClaimHash,
+		) -> Option<(AccountId, BlockNumber)> {
+			PoeModule::collection_claim_of(collection, &hash)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -482,7 +565,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
-			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, pallet_poe, PoeModule);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)